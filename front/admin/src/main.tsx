@@ -8,6 +8,9 @@ import { createRoot } from 'react-dom/client'
 import { BrowserRouter } from 'react-router-dom'
 import App from './App.tsx'
 import './index.css'
+import { initTelemetry } from './utils/telemetry'
+
+initTelemetry()
 
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
@@ -0,0 +1,266 @@
+/**
+ * 浏览器端遥测：生成 span 与错误日志，按 OTLP/HTTP JSON 批量上报到后端
+ * `/api/common/telemetry`，由服务端过滤属性后经其导出器转发。
+ * `tracedFetch` 在请求头写入 W3C `traceparent`，后端请求 span 与前端交互处于同一条 trace。
+ * 目前 `api/` 下的接口仍走 `mockServer`，尚无调用方；接入真实后端时应统一经由 `tracedFetch` 发起请求。
+ */
+
+const ENDPOINT = '/api/common/telemetry/v1'
+const SCOPE = { name: 'admin-web', version: '1.0.0' }
+const FLUSH_INTERVAL_MS = 5000
+const MAX_QUEUE = 200
+const SESSION_KEY = 'telemetry_session_id'
+
+type AttrValue = string | number | boolean
+type Attributes = Record<string, AttrValue>
+
+interface OtlpKeyValue {
+  key: string
+  value: { stringValue: string } | { boolValue: boolean } | { intValue: string } | { doubleValue: number }
+}
+
+interface OtlpSpan {
+  traceId: string
+  spanId: string
+  parentSpanId?: string
+  name: string
+  kind: number
+  startTimeUnixNano: string
+  endTimeUnixNano: string
+  attributes: OtlpKeyValue[]
+  events: { timeUnixNano: string; name: string; attributes: OtlpKeyValue[] }[]
+  status: { code: number; message?: string }
+}
+
+interface OtlpLogRecord {
+  timeUnixNano: string
+  severityNumber: number
+  severityText: string
+  body: { stringValue: string }
+  attributes: OtlpKeyValue[]
+  traceId?: string
+  spanId?: string
+}
+
+/** OTLP SpanKind */
+const SPAN_KIND = { internal: 1, client: 3 } as const
+/** OTLP StatusCode */
+const STATUS = { unset: 0, ok: 1, error: 2 } as const
+
+const spanQueue: OtlpSpan[] = []
+const logQueue: OtlpLogRecord[] = []
+let started = false
+
+function randomHex(bytes: number): string {
+  const buf = crypto.getRandomValues(new Uint8Array(bytes))
+  return Array.from(buf, (b) => b.toString(16).padStart(2, '0')).join('')
+}
+
+/** 当前时间（Unix 纳秒），OTLP JSON 中 64 位整数以字符串表示 */
+function nowNanos(): string {
+  const micros = Math.round((performance.timeOrigin + performance.now()) * 1000)
+  return (BigInt(micros) * 1000n).toString()
+}
+
+function toKeyValues(attributes: Attributes): OtlpKeyValue[] {
+  return Object.entries(attributes).map(([key, v]) => {
+    if (typeof v === 'boolean') return { key, value: { boolValue: v } }
+    if (typeof v === 'number') {
+      return Number.isInteger(v) ? { key, value: { intValue: String(v) } } : { key, value: { doubleValue: v } }
+    }
+    return { key, value: { stringValue: v } }
+  })
+}
+
+function sessionId(): string {
+  let id = sessionStorage.getItem(SESSION_KEY)
+  if (!id) {
+    id = randomHex(16)
+    sessionStorage.setItem(SESSION_KEY, id)
+  }
+  return id
+}
+
+/** 浏览器信息作为资源属性上报，服务端按白名单保留并附加到每条数据 */
+function resourceAttributes(): OtlpKeyValue[] {
+  return toKeyValues({
+    'browser.language': navigator.language,
+    'user_agent.original': navigator.userAgent,
+    'session.id': sessionId(),
+  })
+}
+
+export class Span {
+  readonly traceId: string
+  readonly spanId: string
+  private readonly data: OtlpSpan
+  private ended = false
+
+  constructor(name: string, kind: number, attributes: Attributes = {}, parent?: Span) {
+    this.traceId = parent?.traceId ?? randomHex(16)
+    this.spanId = randomHex(8)
+    this.data = {
+      traceId: this.traceId,
+      spanId: this.spanId,
+      parentSpanId: parent?.spanId,
+      name,
+      kind,
+      startTimeUnixNano: nowNanos(),
+      endTimeUnixNano: '',
+      attributes: toKeyValues({ 'page.url': location.pathname, ...attributes }),
+      events: [],
+      status: { code: STATUS.unset },
+    }
+  }
+
+  /** W3C traceparent，始终标记为采样 */
+  get traceparent(): string {
+    return `00-${this.traceId}-${this.spanId}-01`
+  }
+
+  setAttributes(attributes: Attributes) {
+    this.data.attributes.push(...toKeyValues(attributes))
+  }
+
+  recordException(error: unknown) {
+    const err = error instanceof Error ? error : new Error(String(error))
+    this.data.events.push({
+      timeUnixNano: nowNanos(),
+      name: 'exception',
+      attributes: toKeyValues({
+        'exception.type': err.name,
+        'exception.message': err.message,
+        'exception.stacktrace': err.stack ?? '',
+      }),
+    })
+    this.setError(err.message)
+  }
+
+  setError(message?: string) {
+    this.data.status = { code: STATUS.error, message }
+  }
+
+  end() {
+    if (this.ended) return
+    this.ended = true
+    this.data.endTimeUnixNano = nowNanos()
+    enqueue(spanQueue, this.data)
+  }
+}
+
+/** 开始一个交互 span（如按钮点击），其中发起的 `tracedFetch` 作为子 span */
+export function startSpan(name: string, attributes?: Attributes, parent?: Span): Span {
+  return new Span(name, SPAN_KIND.internal, attributes, parent)
+}
+
+/** 在交互 span 中执行异步操作，异常时记录到 span 并继续抛出 */
+export async function withSpan<T>(name: string, fn: (span: Span) => Promise<T>, attributes?: Attributes): Promise<T> {
+  const span = startSpan(name, attributes)
+  try {
+    return await fn(span)
+  } catch (e) {
+    span.recordException(e)
+    throw e
+  } finally {
+    span.end()
+  }
+}
+
+/** 带 `traceparent` 的 fetch，请求作为 client span 记录 */
+export async function tracedFetch(input: RequestInfo | URL, init: RequestInit = {}, parent?: Span): Promise<Response> {
+  const method = (init.method ?? (input instanceof Request ? input.method : 'GET')).toUpperCase()
+  const url = new URL(input instanceof Request ? input.url : String(input), location.href)
+  const span = new Span(`${method} ${url.pathname}`, SPAN_KIND.client, {
+    'http.request.method': method,
+    'url.full': url.origin + url.pathname,
+    'server.address': url.hostname,
+  }, parent)
+
+  const headers = new Headers(init.headers ?? (input instanceof Request ? input.headers : undefined))
+  headers.set('traceparent', span.traceparent)
+  try {
+    const res = await fetch(input, { ...init, headers })
+    span.setAttributes({ 'http.response.status_code': res.status })
+    if (res.status >= 500) span.setError(`HTTP ${res.status}`)
+    return res
+  } catch (e) {
+    span.recordException(e)
+    throw e
+  } finally {
+    span.end()
+  }
+}
+
+/** 上报一条错误日志，可关联到 span */
+export function logError(message: string, attributes: Attributes = {}, span?: Span) {
+  enqueue(logQueue, {
+    timeUnixNano: nowNanos(),
+    severityNumber: 17,
+    severityText: 'ERROR',
+    body: { stringValue: message },
+    attributes: toKeyValues({ 'page.url': location.pathname, ...attributes }),
+    traceId: span?.traceId,
+    spanId: span?.spanId,
+  })
+}
+
+function enqueue<T>(queue: T[], item: T) {
+  if (!started) return
+  // 队列满时丢弃最旧的数据，避免后端不可用时无限增长
+  if (queue.length >= MAX_QUEUE) queue.shift()
+  queue.push(item)
+}
+
+function send(path: string, body: unknown, beacon: boolean) {
+  const payload = JSON.stringify(body)
+  if (beacon) {
+    navigator.sendBeacon(`${ENDPOINT}/${path}`, new Blob([payload], { type: 'application/json' }))
+    return
+  }
+  // 上报请求本身不注入 traceparent，避免产生递归的 trace
+  fetch(`${ENDPOINT}/${path}`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: payload,
+    keepalive: true,
+  }).catch(() => {})
+}
+
+/** 发送队列中的数据；页面隐藏时改用 sendBeacon，保证关闭页面前的数据能送达 */
+export function flush(beacon = false) {
+  const resource = { attributes: resourceAttributes() }
+  if (spanQueue.length > 0) {
+    const spans = spanQueue.splice(0)
+    send('traces', { resourceSpans: [{ resource, scopeSpans: [{ scope: SCOPE, spans }] }] }, beacon)
+  }
+  if (logQueue.length > 0) {
+    const logRecords = logQueue.splice(0)
+    send('logs', { resourceLogs: [{ resource, scopeLogs: [{ scope: SCOPE, logRecords }] }] }, beacon)
+  }
+}
+
+/** 启动定时上报并捕获未处理的异常，在应用入口调用一次 */
+export function initTelemetry() {
+  if (started) return
+  started = true
+
+  window.setInterval(() => flush(), FLUSH_INTERVAL_MS)
+  window.addEventListener('pagehide', () => flush(true))
+  document.addEventListener('visibilitychange', () => {
+    if (document.visibilityState === 'hidden') flush(true)
+  })
+
+  window.addEventListener('error', (event) => {
+    logError(event.message, {
+      'exception.type': event.error instanceof Error ? event.error.name : 'Error',
+      'exception.stacktrace': event.error instanceof Error ? (event.error.stack ?? '') : '',
+    })
+  })
+  window.addEventListener('unhandledrejection', (event) => {
+    const err = event.reason instanceof Error ? event.reason : new Error(String(event.reason))
+    logError(err.message, {
+      'exception.type': err.name,
+      'exception.stacktrace': err.stack ?? '',
+    })
+  })
+}
@@ -1,12 +1,14 @@
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use chrono::{Duration, Utc};
+use std::time::Instant;
 
 use crate::api::common::captcha;
 use crate::app::AppState;
 use crate::config::RAW_CONFIG;
 use crate::error::AppError;
 use crate::repositories::admin::AdminRepository;
+use crate::telemetry::{metrics, runtime_metrics};
 
 use super::dto::{AdminInfo, LoginResponse, TokenResponse};
 
@@ -14,7 +16,7 @@ use super::dto::{AdminInfo, LoginResponse, TokenResponse};
 fn get_jwt_config() -> (String, i64) {
     let config = RAW_CONFIG.get().expect("Config not initialized");
     let secret = config.get_string("jwt.secret").unwrap_or_else(|_| "default-secret".to_string());
-    let expire_seconds = config.get_int("jwt.expire_seconds").unwrap_or(86400) as i64;
+    let expire_seconds = config.get_int("jwt.expire_seconds").unwrap_or(86400);
     (secret, expire_seconds)
 }
 
@@ -39,26 +41,42 @@ impl AuthService {
         password: String,
         altcha: String,
     ) -> Result<LoginResponse, AppError> {
-        captcha::verify_client_payload(&altcha)?;
+        let started = Instant::now();
+        let record = |outcome| metrics::business().record_login(outcome, started.elapsed());
+
+        captcha::verify_client_payload(&altcha).inspect_err(|_| record("captcha_failed"))?;
 
         // 查询管理员
         let admin = AdminRepository::find_by_username(&state.db, &username)
-            .await?
-            .ok_or_else(|| AppError::AuthFailed("用户名或密码错误".into()))?;
+            .await
+            .inspect_err(|_| record("error"))?
+            .ok_or_else(|| {
+                record("unknown_user");
+                AppError::AuthFailed("用户名或密码错误".into())
+            })?;
 
         // 检查状态
         if admin.status == 0 {
+            record("disabled");
             return Err(AppError::AuthFailed("账号已被禁用".into()));
         }
 
-        // 验证密码
+        // 验证密码：bcrypt 耗时数百毫秒，放到阻塞线程池避免占住 worker
         let password_hash = admin.password_hash.clone();
-        let is_valid = bcrypt::verify(&password, &password_hash)
-            .map_err(|_| AppError::AuthFailed("密码验证失败".into()))?;
+        let is_valid = runtime_metrics::spawn_blocking(move || bcrypt::verify(&password, &password_hash))
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .ok_or_else(|| {
+                record("error");
+                AppError::AuthFailed("密码验证失败".into())
+            })?;
 
         if !is_valid {
+            record("wrong_password");
             return Err(AppError::AuthFailed("用户名或密码错误".into()));
         }
+        record("success");
 
         // 更新最后登录时间
         let _ = AdminRepository::update_last_login(&state.db, admin.id).await;
@@ -1,6 +1,7 @@
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::category::CategoryRepository;
+use crate::telemetry::metrics;
 use super::dto::{CreateCategoryRequest, UpdateCategoryRequest, CategoryResponse};
 
 pub struct CategoryService;
@@ -38,6 +39,7 @@ impl CategoryService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("category", "create");
         Ok(CategoryResponse::from(category))
     }
 
@@ -64,6 +66,7 @@ impl CategoryService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("category", "update");
         Ok(CategoryResponse::from(category))
     }
 
@@ -72,6 +75,7 @@ impl CategoryService {
         CategoryRepository::delete(&state.db, id)
             .await
             .map_err(AppError::from)?;
+        metrics::business().record_content("category", "delete");
         Ok(())
     }
 }
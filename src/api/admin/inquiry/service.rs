@@ -1,6 +1,10 @@
+use opentelemetry::KeyValue;
+
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::inquiry::InquiryRepository;
+use crate::repositories::product::ProductRepository;
+use crate::telemetry::metrics;
 use super::dto::{BatchDeleteRequest, CreateInquiryRequest, InquiryResponse, PaginatedInquiryResponse};
 
 pub struct InquiryService;
@@ -48,6 +52,19 @@ impl InquiryService {
         .await
         .map_err(AppError::from)?;
 
+        // 公开接口的 product_id / product_name 都由客户端填写，只有已存在的产品 ID 才作为指标标签；
+        // 查询失败只影响标签，不影响提交结果
+        let product = match inquiry.product_id {
+            None => "none".to_string(),
+            Some(id) => ProductRepository::find_by_id(&state.db, id)
+                .await
+                .ok()
+                .flatten()
+                .map_or_else(|| "unknown".to_string(), |product| product.id.to_string()),
+        };
+        metrics::business()
+            .inquiries_created
+            .add(1, &[KeyValue::new("product.id", product)]);
         Ok(InquiryResponse::from(inquiry))
     }
 
@@ -79,3 +96,48 @@ impl InquiryService {
         Ok(result.rows_affected)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::telemetry::metrics::testing;
+
+    fn request(product_id: Option<i32>) -> CreateInquiryRequest {
+        CreateInquiryRequest {
+            name: "Alice".to_string(),
+            email: Some("alice@example.com".to_string()),
+            phone: None,
+            message: "Price?".to_string(),
+            product_id,
+            product_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_inquiry_counts_by_product() {
+        let installed = testing::install().await;
+        // 没有 products 表：产品查询失败只影响指标标签，询盘照常保存
+        let state = AppState {
+            db: crate::db::init_db("sqlite::memory:").await.unwrap(),
+        };
+
+        let inquiry = InquiryService::create_inquiry(&state, request(Some(42)))
+            .await
+            .unwrap();
+        assert_eq!(inquiry.product_id, Some(42));
+        InquiryService::create_inquiry(&state, request(None))
+            .await
+            .unwrap();
+
+        let mut created = installed.counter("app.inquiries.created");
+        created.sort_by(|a, b| a.0[0].value.as_str().cmp(&b.0[0].value.as_str()));
+        assert_eq!(
+            created,
+            vec![
+                (vec![KeyValue::new("product.id", "none")], 1),
+                (vec![KeyValue::new("product.id", "unknown")], 1),
+            ]
+        );
+    }
+}
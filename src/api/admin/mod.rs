@@ -8,6 +8,7 @@ pub mod tag;
 pub mod news;
 pub mod page;
 pub mod inquiry;
+pub mod telemetry;
 
 use axum::middleware;
 use axum::Router;
@@ -33,5 +34,6 @@ pub fn protected_routes(state: AppState) -> Router<AppState> {
         .merge(news::routes())
         .merge(page::routes())
         .merge(inquiry::protected_routes())
+        .merge(telemetry::routes())
         .layer(middleware::from_fn_with_state(state, auth_middleware::auth_middleware))
 }
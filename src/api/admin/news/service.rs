@@ -3,6 +3,7 @@ use chrono::NaiveDateTime;
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::news::NewsRepository;
+use crate::telemetry::metrics;
 use super::dto::{CreateNewsRequest, UpdateNewsRequest, NewsResponse, PaginatedNewsResponse};
 
 pub struct NewsService;
@@ -71,6 +72,7 @@ impl NewsService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("news", "create");
         Ok(NewsResponse::from(news))
     }
 
@@ -110,6 +112,7 @@ impl NewsService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("news", "update");
         Ok(NewsResponse::from(news))
     }
 
@@ -118,6 +121,7 @@ impl NewsService {
         NewsRepository::delete(&state.db, id)
             .await
             .map_err(AppError::from)?;
+        metrics::business().record_content("news", "delete");
         Ok(())
     }
 }
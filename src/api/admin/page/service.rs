@@ -1,6 +1,7 @@
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::page::PageRepository;
+use crate::telemetry::metrics;
 use super::dto::{CreatePageRequest, UpdatePageRequest, PageResponse, PaginatedPageResponse};
 
 pub struct PageService;
@@ -49,6 +50,7 @@ impl PageService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("page", "create");
         Ok(PageResponse::from(page))
     }
 
@@ -76,6 +78,7 @@ impl PageService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("page", "update");
         Ok(PageResponse::from(page))
     }
 
@@ -84,6 +87,7 @@ impl PageService {
         PageRepository::delete(&state.db, id)
             .await
             .map_err(AppError::from)?;
+        metrics::business().record_content("page", "delete");
         Ok(())
     }
 }
@@ -6,6 +6,7 @@ use crate::error::AppError;
 use crate::repositories::product::ProductRepository;
 use crate::repositories::product_tag::ProductTagRepository;
 use crate::repositories::tag::TagRepository;
+use crate::telemetry::metrics;
 use super::dto::{CreateProductRequest, UpdateProductRequest, ProductResponse, ProductWithTagsResponse, SetProductTagsRequest, PaginatedProductResponse};
 
 pub struct ProductService;
@@ -96,6 +97,7 @@ impl ProductService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("product", "create");
         Ok(ProductResponse::from(product))
     }
 
@@ -129,6 +131,7 @@ impl ProductService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("product", "update");
         Ok(ProductResponse::from(product))
     }
 
@@ -142,6 +145,7 @@ impl ProductService {
         ProductRepository::delete(&state.db, id)
             .await
             .map_err(AppError::from)?;
+        metrics::business().record_content("product", "delete");
         Ok(())
     }
 
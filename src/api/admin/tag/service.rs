@@ -1,6 +1,7 @@
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::tag::TagRepository;
+use crate::telemetry::metrics;
 use super::dto::{CreateTagRequest, UpdateTagRequest, TagResponse};
 
 pub struct TagService;
@@ -35,6 +36,7 @@ impl TagService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("tag", "create");
         Ok(TagResponse::from(tag))
     }
 
@@ -58,6 +60,7 @@ impl TagService {
         )
         .await
         .map_err(AppError::from)?;
+        metrics::business().record_content("tag", "update");
         Ok(TagResponse::from(tag))
     }
 
@@ -66,6 +69,7 @@ impl TagService {
         TagRepository::delete(&state.db, id)
             .await
             .map_err(AppError::from)?;
+        metrics::business().record_content("tag", "delete");
         Ok(())
     }
 }
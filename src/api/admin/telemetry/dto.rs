@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::log_filter::FilterSnapshot;
+
+/// 调整日志过滤规则请求
+#[derive(Debug, Deserialize)]
+pub struct UpdateLogFilterRequest {
+    /// `RUST_LOG` 语法，如 `info,sea_orm=debug`
+    pub directives: String,
+    /// 生效时长（秒），到期自动回退到启动时的规则；不填则一直生效
+    pub ttl_secs: Option<u64>,
+}
+
+/// 日志过滤规则响应
+#[derive(Debug, Serialize)]
+pub struct LogFilterResponse {
+    pub directives: String,
+    pub default: String,
+    /// 临时规则的到期时间，为空表示长期生效
+    pub expires_at: Option<String>,
+}
+
+impl From<FilterSnapshot> for LogFilterResponse {
+    fn from(snapshot: FilterSnapshot) -> Self {
+        Self {
+            directives: snapshot.current,
+            default: snapshot.default,
+            expires_at: snapshot
+                .expires_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
+        }
+    }
+}
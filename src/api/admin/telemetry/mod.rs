@@ -0,0 +1,71 @@
+//! 后台可观测性管理模块：运行时调整采样、日志过滤等配置
+
+pub mod dto;
+mod service;
+
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+
+use crate::app::AppState;
+use crate::config::SamplingConfig;
+use crate::dto::response::ApiResponse;
+use crate::error::AppError;
+use dto::UpdateLogFilterRequest;
+use service::TelemetryService;
+
+/// GET /admin/telemetry/sampling - 获取当前采样配置
+#[tracing::instrument(skip_all)]
+pub async fn get_sampling() -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(TelemetryService::get_sampling()))
+}
+
+/// PUT /admin/telemetry/sampling - 热更新采样配置
+#[tracing::instrument(skip_all)]
+pub async fn update_sampling(
+    axum::Json(payload): axum::Json<SamplingConfig>,
+) -> Result<impl IntoResponse, AppError> {
+    let config = TelemetryService::update_sampling(payload)?;
+    tracing::info!(strategy = ?config.strategy, ratio = config.ratio, "Sampling config reloaded");
+    Ok(ApiResponse::success(config))
+}
+
+/// GET /admin/telemetry/log-filter - 获取当前日志过滤规则
+#[tracing::instrument(skip_all)]
+pub async fn get_log_filter() -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(TelemetryService::get_log_filter()?))
+}
+
+/// PUT /admin/telemetry/log-filter - 调整日志过滤规则，可设置到期自动回退
+#[tracing::instrument(skip_all)]
+pub async fn update_log_filter(
+    axum::Json(payload): axum::Json<UpdateLogFilterRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let filter = TelemetryService::update_log_filter(payload)?;
+    tracing::info!(
+        directives = %filter.directives,
+        expires_at = ?filter.expires_at,
+        "Log filter reloaded"
+    );
+    Ok(ApiResponse::success(filter))
+}
+
+/// DELETE /admin/telemetry/log-filter - 立即恢复启动时的日志过滤规则
+#[tracing::instrument(skip_all)]
+pub async fn reset_log_filter() -> Result<impl IntoResponse, AppError> {
+    let filter = TelemetryService::reset_log_filter()?;
+    tracing::info!(directives = %filter.directives, "Log filter reset");
+    Ok(ApiResponse::success(filter))
+}
+
+/// 构建后台可观测性管理路由
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/telemetry/sampling", get(get_sampling).put(update_sampling))
+        .route(
+            "/telemetry/log-filter",
+            get(get_log_filter)
+                .put(update_log_filter)
+                .delete(reset_log_filter),
+        )
+}
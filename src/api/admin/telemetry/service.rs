@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use super::dto::{LogFilterResponse, UpdateLogFilterRequest};
+use crate::config::SamplingConfig;
+use crate::error::AppError;
+use crate::telemetry::{log_filter, sampling};
+
+pub struct TelemetryService;
+
+impl TelemetryService {
+    /// 获取当前采样配置
+    pub fn get_sampling() -> SamplingConfig {
+        sampling::current().as_ref().clone()
+    }
+
+    /// 校验并替换采样配置
+    pub fn update_sampling(config: SamplingConfig) -> Result<SamplingConfig, AppError> {
+        sampling::validate(&config).map_err(AppError::Validation)?;
+        sampling::reload(config.clone());
+        Ok(config)
+    }
+
+    /// 获取当前日志过滤规则
+    pub fn get_log_filter() -> Result<LogFilterResponse, AppError> {
+        log_filter::current()
+            .map(Into::into)
+            .ok_or_else(|| AppError::Internal("日志过滤器未初始化".to_string()))
+    }
+
+    /// 替换日志过滤规则，可选到期自动回退
+    pub fn update_log_filter(req: UpdateLogFilterRequest) -> Result<LogFilterResponse, AppError> {
+        if req.ttl_secs == Some(0) {
+            return Err(AppError::Validation("ttl_secs 必须大于 0".to_string()));
+        }
+        log_filter::update(&req.directives, req.ttl_secs.map(Duration::from_secs))
+            .map(Into::into)
+            .map_err(AppError::Validation)
+    }
+
+    /// 立即回退到启动时的日志过滤规则
+    pub fn reset_log_filter() -> Result<LogFilterResponse, AppError> {
+        log_filter::reset().map(Into::into).map_err(AppError::Internal)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::repositories::admin::AdminRepository;
+use crate::telemetry::runtime_metrics;
 use super::dto::{AdminUserResponse, CreateAdminRequest, UpdateAdminRequest};
 
 pub struct AdminUserService;
@@ -28,8 +29,7 @@ impl AdminUserService {
         state: &AppState,
         req: CreateAdminRequest,
     ) -> Result<AdminUserResponse, AppError> {
-        let password_hash = bcrypt::hash(&req.password, bcrypt::DEFAULT_COST)
-            .map_err(|_| AppError::Internal("密码加密失败".to_string()))?;
+        let password_hash = hash_password(req.password).await?;
         let role = req.role.unwrap_or_else(|| "admin".to_string());
         let admin = AdminRepository::create(&state.db, req.username, password_hash, req.nickname, role)
             .await
@@ -67,8 +67,7 @@ impl AdminUserService {
             .map_err(AppError::from)?
             .ok_or(AppError::NotFound("管理员不存在".to_string()))?;
 
-        let password_hash = bcrypt::hash(new_password, bcrypt::DEFAULT_COST)
-            .map_err(|_| AppError::Internal("密码加密失败".to_string()))?;
+        let password_hash = hash_password(new_password.to_string()).await?;
 
         use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel};
         let mut active_model = existing.into_active_model();
@@ -85,3 +84,12 @@ impl AdminUserService {
         Ok(())
     }
 }
+
+/// bcrypt 哈希耗时数百毫秒，放到阻塞线程池执行
+async fn hash_password(password: String) -> Result<String, AppError> {
+    runtime_metrics::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .ok()
+        .and_then(Result::ok)
+        .ok_or_else(|| AppError::Internal("密码加密失败".to_string()))
+}
@@ -1,6 +1,6 @@
 //! ALTCHA PoW：签发挑战与校验登录时提交的 `altcha` 字段
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use altcha::{
     create_challenge, verify_solution, Challenge, CreateChallengeOptions, Payload,
@@ -8,10 +8,12 @@ use altcha::{
 };
 use base64::engine::general_purpose::STANDARD as B64;
 use base64::Engine;
+use opentelemetry::KeyValue;
 use rand::RngExt;
 
 use crate::config::RAW_CONFIG;
 use crate::error::AppError;
+use crate::telemetry::metrics;
 
 fn load_secrets() -> Result<(String, String), AppError> {
     let c = RAW_CONFIG
@@ -50,20 +52,41 @@ pub fn create_signed_challenge() -> Result<Challenge, AppError> {
         ..Default::default()
     };
 
-    create_challenge(options).map_err(|e| AppError::Internal(format!("签发 ALTCHA 失败: {e}")))
+    let started = Instant::now();
+    let challenge = create_challenge(options)
+        .map_err(|e| AppError::Internal(format!("签发 ALTCHA 失败: {e}")))?;
+
+    let business = metrics::business();
+    business.captcha_challenges.add(1, &[]);
+    business
+        .captcha_challenge_duration
+        .record(started.elapsed().as_secs_f64(), &[]);
+    Ok(challenge)
 }
 
 /// 校验 `alt`：Base64 JSON `Payload`（`challenge` + `solution`）
 pub fn verify_client_payload(alt: &str) -> Result<(), AppError> {
+    let (result, outcome) = match verify(alt) {
+        Ok(()) => ("verified", Ok(())),
+        Err((result, e)) => (result, Err(e)),
+    };
+    metrics::business()
+        .captcha_verifications
+        .add(1, &[KeyValue::new("result", result)]);
+    outcome
+}
+
+/// 校验失败时同时返回用于指标的结果分类
+fn verify(alt: &str) -> Result<(), (&'static str, AppError)> {
     if alt.trim().is_empty() {
-        return Err(AppError::Validation("请完成人机验证".into()));
+        return Err(("missing", AppError::Validation("请完成人机验证".into())));
     }
     let bytes = B64
         .decode(alt)
-        .map_err(|_| AppError::Validation("验证码数据无效（Base64）".into()))?;
+        .map_err(|_| ("malformed", AppError::Validation("验证码数据无效（Base64）".into())))?;
     let payload: Payload = serde_json::from_slice(&bytes)
-        .map_err(|_| AppError::Validation("验证码数据格式错误".into()))?;
-    let (hmac, key) = load_secrets()?;
+        .map_err(|_| ("malformed", AppError::Validation("验证码数据格式错误".into())))?;
+    let (hmac, key) = load_secrets().map_err(|e| ("error", e))?;
     let r = verify_solution(VerifySolutionOptions {
         hmac_key_signature_secret: Some(key),
         ..VerifySolutionOptions::new(
@@ -72,16 +95,19 @@ pub fn verify_client_payload(alt: &str) -> Result<(), AppError> {
             hmac.as_str(),
         )
     })
-    .map_err(|e| AppError::Validation(format!("验证码校验失败: {e}")))?;
+    .map_err(|e| ("error", AppError::Validation(format!("验证码校验失败: {e}"))))?;
 
     if r.expired {
-        return Err(AppError::Validation("验证已过期，请刷新后重试".into()));
+        return Err(("expired", AppError::Validation("验证已过期，请刷新后重试".into())));
     }
     if !r.verified {
         if r.invalid_signature == Some(true) {
-            return Err(AppError::Validation("验证码签名校验未通过".into()));
+            return Err((
+                "invalid_signature",
+                AppError::Validation("验证码签名校验未通过".into()),
+            ));
         }
-        return Err(AppError::Validation("人机验证未通过，请重试".into()));
+        return Err(("failed", AppError::Validation("人机验证未通过，请重试".into())));
     }
     Ok(())
 }
@@ -1,6 +1,7 @@
 //! 公共 API 模块 - 前后台共用的接口
 
 pub mod captcha;
+pub mod telemetry;
 pub mod upload;
 
 use axum::Router;
@@ -11,5 +12,6 @@ use crate::app::AppState;
 pub fn routes() -> Router<AppState> {
     Router::new()
         .merge(captcha::routes())
+        .merge(telemetry::routes())
         .merge(upload::routes())
 }
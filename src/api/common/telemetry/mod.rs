@@ -0,0 +1,36 @@
+//! 浏览器端遥测：接收前端 OTLP/HTTP JSON 格式的 traces 与日志，经服务端导出器转发
+
+mod service;
+
+use axum::Router;
+use axum::extract::Request;
+use axum::response::IntoResponse;
+use axum::routing::post;
+
+use crate::app::AppState;
+use crate::dto::response::ApiResponse;
+use crate::error::AppError;
+use service::TelemetryService;
+
+/// POST /api/common/telemetry/v1/traces - 接收浏览器端 spans
+#[tracing::instrument(skip_all)]
+pub async fn ingest_traces(request: Request) -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(
+        TelemetryService::ingest_traces(request).await?,
+    ))
+}
+
+/// POST /api/common/telemetry/v1/logs - 接收浏览器端日志
+#[tracing::instrument(skip_all)]
+pub async fn ingest_logs(request: Request) -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(
+        TelemetryService::ingest_logs(request).await?,
+    ))
+}
+
+/// 构建浏览器端遥测路由，无需认证，按客户端 IP 限流
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/telemetry/v1/traces", post(ingest_traces))
+        .route("/telemetry/v1/logs", post(ingest_logs))
+}
@@ -0,0 +1,84 @@
+use axum::body::Bytes;
+use axum::extract::Request;
+use axum::http::header::CONTENT_TYPE;
+use http_body_util::LengthLimitError;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use serde::de::DeserializeOwned;
+
+use crate::error::AppError;
+use crate::middleware::client_address;
+use crate::telemetry::browser::{self, Ingested, Pipeline};
+
+/// 浏览器端遥测服务
+pub struct TelemetryService;
+
+impl TelemetryService {
+    /// 校验并转发 traces
+    pub async fn ingest_traces(request: Request) -> Result<Ingested, AppError> {
+        let (pipeline, request) = Self::parse::<ExportTraceServiceRequest>(request).await?;
+        let ingested = pipeline.ingest_traces(request);
+        tracing::debug!(
+            accepted = ingested.accepted,
+            rejected = ingested.rejected,
+            "Browser spans ingested"
+        );
+        Ok(ingested)
+    }
+
+    /// 校验并转发日志
+    pub async fn ingest_logs(request: Request) -> Result<Ingested, AppError> {
+        let (pipeline, request) = Self::parse::<ExportLogsServiceRequest>(request).await?;
+        let ingested = pipeline.ingest_logs(request);
+        tracing::debug!(
+            accepted = ingested.accepted,
+            rejected = ingested.rejected,
+            "Browser logs ingested"
+        );
+        Ok(ingested)
+    }
+
+    /// 依次检查启用状态、限流、内容类型与大小，再解析 OTLP JSON
+    async fn parse<T: DeserializeOwned>(
+        request: Request,
+    ) -> Result<(&'static Pipeline, T), AppError> {
+        let pipeline =
+            browser::pipeline().ok_or_else(|| AppError::NotFound("浏览器端遥测未启用".into()))?;
+
+        // 按 TCP 对端（或受信任代理转发的地址）限流，客户端自填的 X-Forwarded-For 不参与
+        let client = client_address(&request)
+            .map(|ip| ip.to_string())
+            .unwrap_or_default();
+        pipeline.check_rate(&client).map_err(|wait| {
+            AppError::TooManyRequests(format!(
+                "上报过于频繁，请 {} 秒后重试",
+                wait.as_secs().max(1)
+            ))
+        })?;
+
+        let is_json = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if !is_json {
+            return Err(AppError::Validation(
+                "仅支持 application/json 格式的 OTLP 数据".into(),
+            ));
+        }
+
+        let body = Self::read_body(request, pipeline.max_body_bytes()).await?;
+        let parsed = serde_json::from_slice(&body)
+            .map_err(|e| AppError::Validation(format!("OTLP JSON 解析失败: {e}")))?;
+        Ok((pipeline, parsed))
+    }
+
+    async fn read_body(request: Request, limit: usize) -> Result<Bytes, AppError> {
+        axum::body::to_bytes(request.into_body(), limit)
+            .await
+            .map_err(|e| match e.into_inner().downcast::<LengthLimitError>() {
+                Ok(_) => AppError::PayloadTooLarge(format!("请求体超过 {limit} 字节")),
+                Err(e) => AppError::Validation(format!("读取请求体失败: {e}")),
+            })
+    }
+}
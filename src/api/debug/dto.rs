@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+/// trace / 错误 span 查询参数
+#[derive(Debug, Deserialize)]
+pub struct TraceQuery {
+    /// 路由模板，以 `*` 结尾表示前缀匹配
+    pub route: Option<String>,
+    /// 状态码（`500`）、状态码类别（`5xx`）或 `error`
+    pub status: Option<String>,
+    /// 最短耗时（毫秒），trace 按根 span 计
+    pub min_duration_ms: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+/// 日志查询参数
+#[derive(Debug, Deserialize)]
+pub struct LogQuery {
+    /// 路由模板，以 `*` 结尾表示前缀匹配
+    pub route: Option<String>,
+    /// 最低级别：trace / debug / info / warn / error
+    pub level: Option<String>,
+    pub trace_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// CPU profile 参数
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    /// 采集时长（秒），默认 30，不超过 `telemetry.pprof.max_seconds`
+    pub seconds: Option<u64>,
+}
@@ -0,0 +1,83 @@
+//! 开发调试模块：进程内 trace 与日志查看、按需 profile（需认证）
+
+mod dto;
+mod service;
+
+use axum::Router;
+use axum::extract::Query;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::middleware;
+use axum::response::IntoResponse;
+use axum::routing::get;
+
+use crate::app::AppState;
+use crate::dto::response::ApiResponse;
+use crate::error::AppError;
+use crate::middleware::auth as auth_middleware;
+use dto::{LogQuery, ProfileQuery, TraceQuery};
+use service::DebugService;
+
+/// GET /debug/traces - 最近完成的 trace，支持按路由、状态、最短耗时过滤
+#[tracing::instrument(skip_all)]
+pub async fn list_traces(Query(query): Query<TraceQuery>) -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(DebugService::list_traces(query)?))
+}
+
+/// GET /debug/traces/errors - 最近的错误 span
+#[tracing::instrument(skip_all)]
+pub async fn list_error_spans(
+    Query(query): Query<TraceQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(DebugService::list_error_spans(query)?))
+}
+
+/// GET /debug/logs - 最近的日志，支持按路由、最低级别、trace_id 过滤
+#[tracing::instrument(skip_all)]
+pub async fn list_logs(Query(query): Query<LogQuery>) -> Result<impl IntoResponse, AppError> {
+    Ok(ApiResponse::success(DebugService::list_logs(query)?))
+}
+
+/// GET /debug/pprof/profile - 采集 N 秒 CPU profile，返回 gzip 压缩的 pprof protobuf
+#[tracing::instrument(skip_all)]
+pub async fn cpu_profile(Query(query): Query<ProfileQuery>) -> Result<impl IntoResponse, AppError> {
+    let profile = DebugService::cpu_profile(query).await?;
+    Ok(pprof_response(
+        profile,
+        "attachment; filename=\"profile.pb.gz\"",
+    ))
+}
+
+/// GET /debug/pprof/heap - 当前存活内存的堆 profile（需以 `jemalloc` feature 编译）
+#[tracing::instrument(skip_all)]
+pub async fn heap_profile() -> Result<impl IntoResponse, AppError> {
+    let profile = DebugService::heap_profile().await?;
+    Ok(pprof_response(
+        profile,
+        "attachment; filename=\"heap.pb.gz\"",
+    ))
+}
+
+/// 二进制下载响应，可直接交给 `go tool pprof`
+fn pprof_response(profile: Vec<u8>, disposition: &'static str) -> impl IntoResponse {
+    (
+        [
+            (CONTENT_TYPE, "application/octet-stream"),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        profile,
+    )
+}
+
+/// 构建调试路由（需认证）
+pub fn routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/traces", get(list_traces))
+        .route("/traces/errors", get(list_error_spans))
+        .route("/logs", get(list_logs))
+        .route("/pprof/profile", get(cpu_profile))
+        .route("/pprof/heap", get(heap_profile))
+        .layer(middleware::from_fn_with_state(
+            state,
+            auth_middleware::auth_middleware,
+        ))
+}
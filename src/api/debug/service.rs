@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use super::dto::{LogQuery, ProfileQuery, TraceQuery};
+use crate::error::AppError;
+use crate::telemetry::pprof::{self, ProfileError};
+use crate::telemetry::zpages::{self, ErrorSpan, LogFilter, LogRecord, TraceFilter, TraceRecord};
+
+/// 默认返回条数
+const DEFAULT_LIMIT: usize = 50;
+/// 单次最多返回条数
+const MAX_LIMIT: usize = 500;
+/// CPU profile 默认采集时长（秒），与 `go tool pprof` 一致
+const DEFAULT_PROFILE_SECONDS: u64 = 30;
+
+pub struct DebugService;
+
+impl DebugService {
+    /// 最近完成的 trace
+    pub fn list_traces(query: TraceQuery) -> Result<Vec<TraceRecord>, AppError> {
+        let limit = limit(query.limit);
+        zpages::traces(&trace_filter(query)?, limit).ok_or_else(disabled)
+    }
+
+    /// 最近的错误 span
+    pub fn list_error_spans(query: TraceQuery) -> Result<Vec<ErrorSpan>, AppError> {
+        let limit = limit(query.limit);
+        zpages::error_spans(&trace_filter(query)?, limit).ok_or_else(disabled)
+    }
+
+    /// 最近的日志
+    pub fn list_logs(query: LogQuery) -> Result<Vec<LogRecord>, AppError> {
+        let level = query
+            .level
+            .map(|level| {
+                level
+                    .parse()
+                    .map_err(|_| AppError::Validation(format!("无效的日志级别 `{level}`")))
+            })
+            .transpose()?;
+        let filter = LogFilter {
+            route: query.route,
+            level,
+            trace_id: query.trace_id,
+        };
+        zpages::logs(&filter, limit(query.limit)).ok_or_else(disabled)
+    }
+
+    /// 采集指定时长的 CPU profile
+    pub async fn cpu_profile(query: ProfileQuery) -> Result<Vec<u8>, AppError> {
+        let settings = pprof::settings().ok_or_else(pprof_disabled)?;
+        let seconds = query
+            .seconds
+            .unwrap_or(DEFAULT_PROFILE_SECONDS.min(settings.max_seconds));
+        if seconds == 0 || seconds > settings.max_seconds {
+            return Err(AppError::Validation(format!(
+                "seconds 须在 1 ~ {} 之间",
+                settings.max_seconds
+            )));
+        }
+        pprof::cpu_profile(Duration::from_secs(seconds), settings.sample_rate)
+            .await
+            .map_err(profile_error)
+    }
+
+    /// 当前存活内存的堆 profile
+    pub async fn heap_profile() -> Result<Vec<u8>, AppError> {
+        pprof::settings().ok_or_else(pprof_disabled)?;
+        pprof::heap_profile().await.map_err(profile_error)
+    }
+}
+
+fn trace_filter(query: TraceQuery) -> Result<TraceFilter, AppError> {
+    Ok(TraceFilter {
+        route: query.route,
+        status: query
+            .status
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(AppError::Validation)?,
+        min_duration_ms: query.min_duration_ms,
+    })
+}
+
+fn limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+fn disabled() -> AppError {
+    AppError::NotFound("zpages 未启用，请设置 telemetry.zpages.enabled".to_string())
+}
+
+fn pprof_disabled() -> AppError {
+    AppError::NotFound("按需 profile 未启用，请设置 telemetry.pprof.enabled".to_string())
+}
+
+fn profile_error(err: ProfileError) -> AppError {
+    match err {
+        ProfileError::Busy => AppError::TooManyRequests(err.to_string()),
+        ProfileError::Reserved | ProfileError::Unsupported => AppError::NotFound(err.to_string()),
+        ProfileError::Failed(msg) => AppError::Internal(msg),
+    }
+}
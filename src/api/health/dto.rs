@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+use crate::telemetry::resilience::PipelineStatus;
+
+/// 就绪检查结果
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub database: bool,
+    pub telemetry: TelemetryHealth,
+}
+
+/// 遥测导出状态，任一导出管道最近一批导出失败即为不健康，不影响 `ready`
+#[derive(Debug, Serialize)]
+pub struct TelemetryHealth {
+    pub healthy: bool,
+    pub pipelines: Vec<PipelineStatus>,
+}
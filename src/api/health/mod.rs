@@ -0,0 +1,31 @@
+//! 健康检查模块：供负载均衡与 Kubernetes 探针使用（无需认证）
+
+mod dto;
+mod service;
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+
+use crate::app::AppState;
+use crate::dto::response::ApiResponse;
+use service::HealthService;
+
+/// GET /health/ready - 数据库可用时返回 200，否则 503；遥测导出状态仅在响应体中报告
+#[tracing::instrument(skip_all)]
+pub async fn ready(State(state): State<AppState>) -> Response {
+    let readiness = HealthService::readiness(&state.db).await;
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    ApiResponse::success_with_status(readiness, status)
+}
+
+/// 构建健康检查路由
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/ready", get(ready))
+}
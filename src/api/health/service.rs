@@ -0,0 +1,30 @@
+use sea_orm::DatabaseConnection;
+
+use super::dto::{Readiness, TelemetryHealth};
+use crate::telemetry::resilience;
+
+pub struct HealthService;
+
+impl HealthService {
+    /// 检查数据库连接并附带遥测导出状态
+    ///
+    /// 只有数据库决定是否就绪：collector 故障时所有副本同时不就绪会把遥测故障放大为整体不可用
+    pub async fn readiness(db: &DatabaseConnection) -> Readiness {
+        let database = match db.ping().await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(error = %e, "Database ping failed");
+                false
+            }
+        };
+        let telemetry = TelemetryHealth {
+            healthy: resilience::healthy(),
+            pipelines: resilience::statuses(),
+        };
+        Readiness {
+            ready: database,
+            database,
+            telemetry,
+        }
+    }
+}
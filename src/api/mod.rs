@@ -2,3 +2,5 @@
 
 pub mod admin;
 pub mod common;
+pub mod debug;
+pub mod health;
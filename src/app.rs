@@ -4,6 +4,8 @@ use sea_orm::DatabaseConnection;
 
 use crate::api::admin;
 use crate::api::common;
+use crate::api::debug;
+use crate::api::health;
 use crate::middleware::log_bodies;
 
 /// 共享应用状态
@@ -21,6 +23,10 @@ pub fn create_router(state: AppState) -> Router {
         .nest("/api/admin", admin::protected_routes(state.clone()))
         // 公共 API - /api/common/*
         .nest("/api/common", common::routes())
+        // 调试 API - /debug/* (需要认证)
+        .nest("/debug", debug::routes(state.clone()))
+        // 健康检查 - /health/*
+        .nest("/health", health::routes())
         .layer(middleware::from_fn(log_bodies))
         .with_state(state)
 }
@@ -1,9 +1,27 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(about = "Axum OpenTelemetry Demo Server")]
 pub struct Cli {
     /// 运行环境，对应 config/{env}.toml 配置文件
-    #[arg(long, default_value = "dev")]
+    #[arg(long, default_value = "dev", global = true)]
     pub env: String,
+
+    /// 不指定子命令时启动服务
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// 从本地 OTLP-JSON 导出文件中打印一条 trace 的 span 树
+    Trace {
+        /// 32 位十六进制 trace_id
+        trace_id: String,
+        /// 导出文件，可重复指定；不指定时读取 telemetry.file_export 目录下的全部 traces 文件
+        #[arg(long = "file")]
+        files: Vec<PathBuf>,
+    },
 }
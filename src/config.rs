@@ -1,7 +1,8 @@
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 use config::{Config, File};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// 全局原始配置对象，业务代码可直接按 key 动态取值
 pub static RAW_CONFIG: OnceLock<Config> = OnceLock::new();
@@ -18,19 +19,916 @@ pub struct AppConfig {
 #[derive(Debug, Deserialize)]
 pub struct ServerConfig {
     pub addr: String,
+    /// 受信任的反向代理（IP 或 CIDR），仅当 TCP 对端属于其中时才采信 `X-Forwarded-*` 头
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
+    /// 慢查询阈值（毫秒），超过时记录脱敏后的 SQL
+    #[serde(default = "default_slow_query_ms")]
+    pub slow_query_ms: u64,
+}
+
+fn default_slow_query_ms() -> u64 {
+    200
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TelemetryConfig {
+    /// 服务标识，写入所有信号的资源属性
+    #[serde(default)]
+    pub service: ServiceConfig,
+    /// 总开关，关闭后 traces / logs / profiling / OTLP metrics 全部停用
     #[serde(default = "default_true")]
     pub otel_enabled: bool,
     pub otel_endpoint: String,
-    pub pyroscope_endpoint: String,
+    /// 日志过滤规则（`RUST_LOG` 语法），设置了 `RUST_LOG` 环境变量时以环境变量为准
+    #[serde(default = "default_log_filter")]
+    pub log_filter: String,
+    /// 控制台日志格式：full / compact / pretty / json
+    #[serde(default)]
+    pub console_format: ConsoleFormat,
+    /// 本地滚动日志文件，与控制台输出同时生效
+    #[serde(default)]
+    pub file: FileLogConfig,
+    /// 本地 OTLP-JSON 文件导出，与 OTLP 导出同时生效
+    #[serde(default)]
+    pub file_export: FileExportConfig,
+    #[serde(default)]
+    pub traces: SignalConfig,
+    #[serde(default)]
+    pub logs: SignalConfig,
+    /// traces / logs 导出失败的重试
+    #[serde(default)]
+    pub export_retry: RetryConfig,
+    #[serde(default)]
+    pub profiling: ProfilingConfig,
+    /// 已废弃，改用 `profiling.endpoint`；未配置新键时仍沿用此值
+    #[serde(default)]
+    pub pyroscope_endpoint: Option<String>,
+    /// OTLP 传输设置（协议、请求头、超时、压缩、TLS），endpoint 未配置时使用 `otel_endpoint`
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+    /// 入站/出站上下文传播格式，按顺序依次提取
+    #[serde(default = "default_propagators")]
+    pub propagators: Vec<Propagator>,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub body_capture: BodyCaptureConfig,
+    /// 进程内 trace / 日志查看器，不依赖 collector
+    #[serde(default)]
+    pub zpages: ZPagesConfig,
+    /// 按需采集 CPU / 堆 profile
+    #[serde(default)]
+    pub pprof: PprofConfig,
+    /// 已认证管理员身份的记录方式
+    #[serde(default)]
+    pub enduser: EnduserConfig,
+    /// 浏览器端 traces / logs 接入
+    #[serde(default)]
+    pub browser: BrowserTelemetryConfig,
+}
+
+/// 服务名、命名空间与实例 ID，多实例部署时在后端据此区分
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceConfig {
+    #[serde(default = "default_service_name")]
+    pub name: String,
+    /// `service.namespace`，如按业务线或团队分组
+    pub namespace: Option<String>,
+    /// `service.instance.id`，不填则每次启动生成随机 UUID
+    pub instance_id: Option<String>,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            name: default_service_name(),
+            namespace: None,
+            instance_id: None,
+        }
+    }
+}
+
+fn default_service_name() -> String {
+    "axum-otel-demo".to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Propagator {
+    /// W3C `traceparent` / `tracestate`
+    TraceContext,
+    /// W3C `baggage`
+    Baggage,
+    /// Zipkin B3 单头 `b3`
+    B3,
+    /// Zipkin B3 多头 `X-B3-*`
+    B3Multi,
+    /// Jaeger `uber-trace-id`
+    Jaeger,
+}
+
+fn default_propagators() -> Vec<Propagator> {
+    vec![Propagator::TraceContext, Propagator::Baggage]
+}
+
+fn default_log_filter() -> String {
+    "info,axum_otel_demo=debug,tower_http=debug,sea_orm=debug,\
+     pyroscope=off,pyroscope_pprofrs=off,Pyroscope=off,log=off"
+        .to_string()
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleFormat {
+    /// 单行文本，带文件与行号
+    #[default]
+    Full,
+    /// 精简单行文本
+    Compact,
+    /// 多行文本，适合本地开发
+    Pretty,
+    /// 每行一个 JSON 对象，供容器日志采集
+    Json,
+}
+
+/// 滚动日志文件：按天或按大小切分，超出数量或保留期的历史文件会被删除
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileLogConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_log_directory")]
+    pub directory: String,
+    /// 文件名前缀，当前文件为 `{prefix}.log`
+    #[serde(default = "default_log_prefix")]
+    pub prefix: String,
+    #[serde(default)]
+    pub rotation: FileRotation,
+    /// 按大小切分时单个文件的上限
+    #[serde(default = "default_log_max_bytes")]
+    pub max_bytes: u64,
+    /// 最多保留的历史文件数，0 表示不限
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    /// 历史文件最长保留天数，0 表示不限
+    #[serde(default = "default_log_max_age_days")]
+    pub max_age_days: u64,
+    #[serde(default = "default_file_format")]
+    pub format: ConsoleFormat,
+}
+
+impl Default for FileLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: default_log_directory(),
+            prefix: default_log_prefix(),
+            rotation: FileRotation::default(),
+            max_bytes: default_log_max_bytes(),
+            max_files: default_log_max_files(),
+            max_age_days: default_log_max_age_days(),
+            format: default_file_format(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileRotation {
+    /// 每天零点（北京时间）切分
+    #[default]
+    Daily,
+    /// 超过 `max_bytes` 时切分
+    Size,
+}
+
+fn default_log_directory() -> String {
+    "logs".to_string()
+}
+
+fn default_log_prefix() -> String {
+    "axum-otel-demo".to_string()
+}
+
+fn default_log_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_log_max_files() -> usize {
+    7
+}
+
+fn default_log_max_age_days() -> u64 {
+    7
+}
+
+fn default_file_format() -> ConsoleFormat {
+    ConsoleFormat::Json
+}
+
+/// 本地 OTLP-JSON 文件导出：每行一个 OTLP 导出请求，离线排查与 CI 中无需 collector
+///
+/// traces 写入 `{prefix}-traces.jsonl`，logs 写入 `{prefix}-logs.jsonl`，切分与清理规则同滚动日志
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileExportConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub traces: bool,
+    #[serde(default = "default_true")]
+    pub logs: bool,
+    #[serde(default = "default_export_directory")]
+    pub directory: String,
+    #[serde(default = "default_log_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_export_rotation")]
+    pub rotation: FileRotation,
+    #[serde(default = "default_log_max_bytes")]
+    pub max_bytes: u64,
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    #[serde(default = "default_log_max_age_days")]
+    pub max_age_days: u64,
+}
+
+impl Default for FileExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            traces: true,
+            logs: true,
+            directory: default_export_directory(),
+            prefix: default_log_prefix(),
+            rotation: default_export_rotation(),
+            max_bytes: default_log_max_bytes(),
+            max_files: default_log_max_files(),
+            max_age_days: default_log_max_age_days(),
+        }
+    }
+}
+
+impl FileExportConfig {
+    /// 单个信号对应的滚动文件配置，`signal` 为 traces / logs
+    pub fn file_config(&self, signal: &str) -> FileLogConfig {
+        FileLogConfig {
+            enabled: self.enabled,
+            directory: self.directory.clone(),
+            prefix: format!("{}-{signal}", self.prefix),
+            rotation: self.rotation,
+            max_bytes: self.max_bytes,
+            max_files: self.max_files,
+            max_age_days: self.max_age_days,
+            format: ConsoleFormat::Json,
+        }
+    }
+}
+
+fn default_export_directory() -> String {
+    "telemetry".to_string()
+}
+
+fn default_export_rotation() -> FileRotation {
+    FileRotation::Size
+}
+
+/// 浏览器端遥测接入：`POST /api/common/telemetry/v1/{traces,logs}` 接收 OTLP/HTTP JSON，
+/// 过滤后以独立的 Resource 经服务端的 OTLP 与文件导出转发
+#[derive(Debug, Clone, Deserialize)]
+pub struct BrowserTelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 浏览器端数据的 `service.name`
+    #[serde(default = "default_browser_service_name")]
+    pub service_name: String,
+    /// 单次请求 body 上限（字节）
+    #[serde(default = "default_browser_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// 单次请求最多接收的 span / 日志条数，超出部分丢弃
+    #[serde(default = "default_browser_max_items")]
+    pub max_items: usize,
+    /// 每个客户端 IP 每分钟允许的请求数
+    #[serde(default = "default_browser_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+    /// 允许的突发请求数
+    #[serde(default = "default_browser_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+    /// 允许保留的属性名，以 `*` 结尾表示前缀匹配，其余属性丢弃
+    #[serde(default = "default_browser_allowed_attributes")]
+    pub allowed_attributes: Vec<String>,
+    /// 字符串属性值的最大长度（字符），超出部分截断
+    #[serde(default = "default_browser_max_attribute_length")]
+    pub max_attribute_length: usize,
+}
+
+impl Default for BrowserTelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            service_name: default_browser_service_name(),
+            max_body_bytes: default_browser_max_body_bytes(),
+            max_items: default_browser_max_items(),
+            rate_limit_per_minute: default_browser_rate_limit_per_minute(),
+            rate_limit_burst: default_browser_rate_limit_burst(),
+            allowed_attributes: default_browser_allowed_attributes(),
+            max_attribute_length: default_browser_max_attribute_length(),
+        }
+    }
+}
+
+fn default_browser_service_name() -> String {
+    "axum-otel-demo-admin".to_string()
+}
+
+fn default_browser_max_body_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_browser_max_items() -> usize {
+    512
+}
+
+fn default_browser_rate_limit_per_minute() -> u32 {
+    60
+}
+
+fn default_browser_rate_limit_burst() -> u32 {
+    20
+}
+
+fn default_browser_allowed_attributes() -> Vec<String> {
+    [
+        "http.*",
+        "url.*",
+        "server.*",
+        "browser.*",
+        "user_agent.*",
+        "session.id",
+        "page.*",
+        "component",
+        "event.name",
+        "exception.*",
+        "error.type",
+        "service.version",
+        "telemetry.sdk.*",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_browser_max_attribute_length() -> usize {
+    1024
+}
+
+/// 已认证管理员身份：请求 span 记录 `enduser.id` / `enduser.role`，
+/// `enduser.id` 同时写入 baggage，请求内后续的 span 与日志都带上该属性
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnduserConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// `enduser.id` 的记录方式
+    #[serde(default)]
+    pub id: EnduserIdMode,
+    /// `hash` 模式的 HMAC 密钥，未配置时读取环境变量 `ENDUSER_HASH_KEY`，两者都没有则启动失败
+    pub hash_key: Option<String>,
+    /// 写入 baggage，供请求内的下游 span 与日志携带
+    #[serde(default = "default_true")]
+    pub baggage: bool,
+}
+
+impl Default for EnduserConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            id: EnduserIdMode::default(),
+            hash_key: None,
+            baggage: true,
+        }
+    }
+}
+
+impl EnduserConfig {
+    /// `hash_key` 的环境变量来源，便于密钥不落在配置文件里
+    const HASH_KEY_ENV: &str = "ENDUSER_HASH_KEY";
+
+    /// `hash` 模式补全密钥：配置优先，其次环境变量；缺少密钥时返回错误，
+    /// 否则每个进程各用一把随机密钥，同一用户在不同实例、重启前后的摘要都对不上
+    fn resolve_hash_key(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        if !self.enabled || self.id != EnduserIdMode::Hash {
+            return Ok(());
+        }
+        let key = self
+            .hash_key
+            .take()
+            .or_else(|| env(Self::HASH_KEY_ENV))
+            .filter(|key| !key.is_empty());
+        match key {
+            Some(key) => {
+                self.hash_key = Some(key);
+                Ok(())
+            }
+            None => Err(format!(
+                "telemetry.enduser.id = \"hash\" requires telemetry.enduser.hash_key or {}",
+                Self::HASH_KEY_ENV
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnduserIdMode {
+    /// 原样记录管理员 ID
+    #[default]
+    Raw,
+    /// 记录 HMAC-SHA256 摘要，同一 ID 摘要不变，可关联但不可反查
+    Hash,
+}
+
+/// 进程内 trace / 日志查看器（zPages 风格），通过 `/debug/traces`、`/debug/logs` 查询
+#[derive(Debug, Clone, Deserialize)]
+pub struct ZPagesConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 最多保留的最近 trace 数
+    #[serde(default = "default_zpages_max_traces")]
+    pub max_traces: usize,
+    /// 最多保留的最近错误 span 数，不随 trace 淘汰
+    #[serde(default = "default_zpages_max_error_spans")]
+    pub max_error_spans: usize,
+    /// 最多保留的最近日志条数
+    #[serde(default = "default_zpages_max_logs")]
+    pub max_logs: usize,
+}
+
+impl Default for ZPagesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_traces: default_zpages_max_traces(),
+            max_error_spans: default_zpages_max_error_spans(),
+            max_logs: default_zpages_max_logs(),
+        }
+    }
+}
+
+fn default_zpages_max_traces() -> usize {
+    200
+}
+
+fn default_zpages_max_error_spans() -> usize {
+    200
+}
+
+fn default_zpages_max_logs() -> usize {
+    2000
+}
+
+/// 按需 profile，通过 `/debug/pprof/profile`、`/debug/pprof/heap` 下载 pprof 格式数据
+#[derive(Debug, Clone, Deserialize)]
+pub struct PprofConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// CPU 采样频率（Hz）
+    #[serde(default = "default_pprof_sample_rate")]
+    pub sample_rate: i32,
+    /// 单次 CPU profile 最长采集时间（秒）
+    #[serde(default = "default_pprof_max_seconds")]
+    pub max_seconds: u64,
+}
+
+impl Default for PprofConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: default_pprof_sample_rate(),
+            max_seconds: default_pprof_max_seconds(),
+        }
+    }
+}
+
+fn default_pprof_sample_rate() -> i32 {
+    100
+}
+
+fn default_pprof_max_seconds() -> u64 {
+    60
+}
+
+/// 单个信号的开关，初始化失败时该信号降级为关闭，不影响其他信号
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignalConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 批量导出设置，OTLP 与文件导出各用一个队列
+    #[serde(default)]
+    pub batch: BatchConfig,
+}
+
+impl Default for SignalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            batch: BatchConfig::default(),
+        }
+    }
+}
+
+/// 批处理器设置，队列满后新数据直接丢弃并计入 `otel.sdk.export.dropped`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchConfig {
+    /// 队列容量（条）
+    #[serde(default = "default_batch_max_queue_size")]
+    pub max_queue_size: usize,
+    /// 单次导出的最大条数
+    #[serde(default = "default_batch_max_export_batch_size")]
+    pub max_export_batch_size: usize,
+    /// 导出间隔（毫秒）
+    #[serde(default = "default_batch_scheduled_delay_ms")]
+    pub scheduled_delay_ms: u64,
+    /// 单批导出（含重试）的总时限（毫秒），单次请求的超时见 `otlp.timeout_ms`
+    #[serde(default = "default_batch_export_timeout_ms")]
+    pub export_timeout_ms: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_queue_size: default_batch_max_queue_size(),
+            max_export_batch_size: default_batch_max_export_batch_size(),
+            scheduled_delay_ms: default_batch_scheduled_delay_ms(),
+            export_timeout_ms: default_batch_export_timeout_ms(),
+        }
+    }
+}
+
+fn default_batch_max_queue_size() -> usize {
+    2048
+}
+
+fn default_batch_max_export_batch_size() -> usize {
+    512
+}
+
+fn default_batch_scheduled_delay_ms() -> u64 {
+    5000
+}
+
+fn default_batch_export_timeout_ms() -> u64 {
+    30000
+}
+
+/// 导出失败的重试，间隔按指数退避并加随机抖动
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryConfig {
+    /// 每批最多尝试次数（含首次），1 表示不重试
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    /// 首次重试前的等待（毫秒）
+    #[serde(default = "default_retry_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// 重试等待的上限（毫秒）
+    #[serde(default = "default_retry_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            initial_backoff_ms: default_retry_initial_backoff_ms(),
+            max_backoff_ms: default_retry_max_backoff_ms(),
+        }
+    }
+}
+
+fn default_retry_max_attempts() -> u32 {
+    5
+}
+
+fn default_retry_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_backoff_ms() -> u64 {
+    5000
+}
+
+/// Pyroscope 持续性能分析
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfilingConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_pyroscope_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_profiling_application_name")]
+    pub application_name: String,
+    /// 采样频率（Hz）
+    #[serde(default = "default_profiling_sample_rate")]
+    pub sample_rate: u32,
+    /// 附加到所有 profile 上的静态标签，`version` 自动填入
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// 请求 span 活跃期间的采样带上 `span_id` / `route` 标签，可从 trace 跳转到火焰图
+    #[serde(default = "default_true")]
+    pub span_labels: bool,
+}
+
+impl Default for ProfilingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            endpoint: default_pyroscope_endpoint(),
+            application_name: default_profiling_application_name(),
+            sample_rate: default_profiling_sample_rate(),
+            tags: HashMap::new(),
+            span_labels: true,
+        }
+    }
+}
+
+fn default_profiling_application_name() -> String {
+    "axum-otel-demo".to_string()
+}
+
+fn default_profiling_sample_rate() -> u32 {
+    100
+}
+
+fn default_pyroscope_endpoint() -> String {
+    "http://localhost:4040".to_string()
+}
+
+/// OTLP 导出器设置，`traces` / `logs` / `metrics` 中未填写的项沿用顶层设置
+///
+/// 标准 `OTEL_EXPORTER_OTLP_*` 环境变量优先于配置文件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OtlpConfig {
+    #[serde(flatten)]
+    pub default: OtlpExporterConfig,
+    #[serde(default)]
+    pub traces: OtlpExporterConfig,
+    #[serde(default)]
+    pub logs: OtlpExporterConfig,
+    #[serde(default)]
+    pub metrics: OtlpExporterConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OtlpExporterConfig {
+    pub protocol: Option<OtlpProtocol>,
+    /// 顶层为 collector 基础地址（HTTP 协议自动追加 `/v1/{signal}`），信号级为完整地址
+    pub endpoint: Option<String>,
+    /// 附加请求头（gRPC metadata），如鉴权用的 `authorization`
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub timeout_ms: Option<u64>,
+    pub compression: Option<OtlpCompression>,
+    pub tls: Option<OtlpTlsConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OtlpProtocol {
+    #[serde(rename = "grpc")]
+    Grpc,
+    #[serde(rename = "http/protobuf")]
+    HttpProtobuf,
+    #[serde(rename = "http/json")]
+    HttpJson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpCompression {
+    None,
+    Gzip,
+    /// 仅 gRPC 支持
+    Zstd,
+}
+
+/// 连接 collector 的 TLS 设置，证书均为 PEM 文件路径
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OtlpTlsConfig {
+    /// 自定义 CA 证书，不填则使用系统根证书
+    pub ca_file: Option<String>,
+    /// 双向 TLS 客户端证书与私钥
+    pub cert_file: Option<String>,
+    pub key_file: Option<String>,
+    /// 覆盖证书校验使用的域名（仅 gRPC）
+    pub domain_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricsConfig {
+    /// 导出方式：otlp 推送到 collector，prometheus 暴露 /metrics 供抓取
+    #[serde(default)]
+    pub exporter: MetricsExporter,
+    /// Prometheus 独立监听地址，不填则 /metrics 挂在主 Router 上
+    pub prometheus_addr: Option<String>,
+    /// 采集 tokio 运行时与进程指标
+    #[serde(default = "default_true")]
+    pub runtime: bool,
+    /// 运行时与进程指标的采集间隔（秒）
+    #[serde(default = "default_runtime_interval_secs")]
+    pub runtime_interval_secs: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            exporter: MetricsExporter::default(),
+            prometheus_addr: None,
+            runtime: true,
+            runtime_interval_secs: default_runtime_interval_secs(),
+        }
+    }
+}
+
+fn default_runtime_interval_secs() -> u64 {
+    10
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsExporter {
+    #[default]
+    Otlp,
+    Prometheus,
+}
+
+/// trace 采样配置，可通过 `PUT /api/admin/telemetry/sampling` 热更新
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SamplingConfig {
+    #[serde(default)]
+    pub strategy: SamplingStrategy,
+    /// ratio / parent_based 策略下根 span 的采样比例
+    #[serde(default = "default_ratio")]
+    pub ratio: f64,
+    /// 未被采样的请求出错时仍然保留整条 trace
+    ///
+    /// 开启后（或配置了慢请求阈值）未采样的 span 会先暂存到本地根 span 结束，
+    /// 最多 10000 个 span、60 秒，约占 10–20 MB 内存；上游明确不采样的 trace 不暂存
+    #[serde(default = "default_true")]
+    pub keep_errors: bool,
+    /// 未被采样的请求耗时超过该阈值时仍然保留整条 trace
+    pub slow_threshold_ms: Option<u64>,
+    /// 按路由覆盖采样比例与慢请求阈值，按顺序匹配第一条
+    #[serde(default)]
+    pub rules: Vec<SamplingRule>,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: SamplingStrategy::default(),
+            ratio: default_ratio(),
+            keep_errors: true,
+            slow_threshold_ms: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategy {
+    AlwaysOn,
+    AlwaysOff,
+    /// 按 trace id 比例采样
+    Ratio,
+    /// 有上游父级时跟随其采样决策，否则按比例采样
+    #[default]
+    ParentBased,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SamplingRule {
+    /// 路由模板，以 `*` 结尾表示前缀匹配，如 `/api/common/*`
+    pub route: String,
+    pub ratio: f64,
+    pub slow_threshold_ms: Option<u64>,
+}
+
+/// 日志中请求/响应 body 与请求头的脱敏规则
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactionConfig {
+    /// 按字段名脱敏（忽略大小写，任意层级）
+    #[serde(default = "default_redact_fields")]
+    pub fields: Vec<String>,
+    /// 按 JSON 路径脱敏，`.` 分隔，`*` 匹配任意字段或数组元素，如 `data.items.*.email`
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// 需要脱敏的请求头（忽略大小写）
+    #[serde(default = "default_redact_headers")]
+    pub headers: Vec<String>,
+    /// 替换后的占位文本
+    #[serde(default = "default_redact_mask")]
+    pub mask: String,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            fields: default_redact_fields(),
+            paths: Vec::new(),
+            headers: default_redact_headers(),
+            mask: default_redact_mask(),
+        }
+    }
+}
+
+/// 请求/响应 body 日志的记录策略
+#[derive(Debug, Clone, Deserialize)]
+pub struct BodyCaptureConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 每个 body 最多记录的字节数，超出部分照常转发但不进日志
+    #[serde(default = "default_body_max_bytes")]
+    pub max_bytes: usize,
+    /// 不记录 body 的 content-type 前缀（忽略大小写）
+    #[serde(default = "default_skip_content_types")]
+    pub skip_content_types: Vec<String>,
+    /// 记录 body 的请求比例，0 表示只按下面的状态码规则记录
+    #[serde(default = "default_ratio")]
+    pub sample_ratio: f64,
+    /// 未被抽中的请求返回 5xx 时仍然记录
+    #[serde(default = "default_true")]
+    pub always_on_server_error: bool,
+    /// 按路由开启/关闭 body 记录，按顺序匹配第一条
+    #[serde(default)]
+    pub rules: Vec<BodyCaptureRule>,
+}
+
+impl Default for BodyCaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: default_body_max_bytes(),
+            skip_content_types: default_skip_content_types(),
+            sample_ratio: default_ratio(),
+            always_on_server_error: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BodyCaptureRule {
+    /// 路由模板，以 `*` 结尾表示前缀匹配
+    pub route: String,
+    pub enabled: bool,
+}
+
+fn default_body_max_bytes() -> usize {
+    4096
+}
+
+fn default_skip_content_types() -> Vec<String> {
+    [
+        "multipart/",
+        "application/octet-stream",
+        "application/pdf",
+        "application/zip",
+        "image/",
+        "audio/",
+        "video/",
+        "font/",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// 覆盖 LoginRequest / ChangePasswordRequest / LoginResponse / TokenResponse 中的敏感字段
+fn default_redact_fields() -> Vec<String> {
+    ["password", "new_password", "altcha", "token", "refresh_token"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_redact_headers() -> Vec<String> {
+    ["authorization", "cookie", "set-cookie"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_redact_mask() -> String {
+    "***".to_string()
+}
+
+fn default_ratio() -> f64 {
+    1.0
 }
 
 fn default_true() -> bool {
@@ -55,8 +953,79 @@ impl AppConfig {
             .set(config.clone())
             .expect("Config already initialized");
 
-        config
+        let mut app: Self = config
+            .clone()
             .try_deserialize()
-            .unwrap_or_else(|e| panic!("Failed to deserialize config: {e}"))
+            .unwrap_or_else(|e| panic!("Failed to deserialize config: {e}"));
+        app.telemetry.migrate_legacy_keys(&config);
+        app.telemetry
+            .enduser
+            .resolve_hash_key(|key| std::env::var(key).ok())
+            .unwrap_or_else(|e| panic!("Invalid config: {e}"));
+        app
+    }
+}
+
+impl TelemetryConfig {
+    /// 旧版配置键映射到新位置，新键优先
+    fn migrate_legacy_keys(&mut self, raw: &Config) {
+        if let Some(endpoint) = &self.pyroscope_endpoint
+            && raw.get_string("telemetry.profiling.endpoint").is_err()
+        {
+            self.profiling.endpoint = endpoint.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use config::FileFormat;
+
+    fn telemetry(toml: &str) -> TelemetryConfig {
+        let raw = Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .unwrap();
+        let mut telemetry: TelemetryConfig = raw.get("telemetry").unwrap();
+        telemetry.migrate_legacy_keys(&raw);
+        telemetry
+    }
+
+    #[test]
+    fn test_legacy_pyroscope_endpoint() {
+        let legacy = "[telemetry]\notel_endpoint = \"http://otel:4317\"\npyroscope_endpoint = \"http://old:4040\"\n";
+        assert_eq!(telemetry(legacy).profiling.endpoint, "http://old:4040");
+
+        let both = format!("{legacy}[telemetry.profiling]\nendpoint = \"http://new:4040\"\n");
+        assert_eq!(telemetry(&both).profiling.endpoint, "http://new:4040");
+    }
+
+    #[test]
+    fn test_enduser_hash_key_required() {
+        let hash = "[telemetry]\notel_endpoint = \"http://otel:4317\"\n[telemetry.enduser]\nid = \"hash\"\n";
+        fn env(value: Option<&'static str>) -> impl Fn(&str) -> Option<String> {
+            move |_| value.map(str::to_owned)
+        }
+
+        let mut config = telemetry(hash).enduser;
+        assert!(config.resolve_hash_key(env(None)).is_err());
+        assert!(config.resolve_hash_key(env(Some(""))).is_err());
+
+        let mut config = telemetry(hash).enduser;
+        config.resolve_hash_key(env(Some("from-env"))).unwrap();
+        assert_eq!(config.hash_key.as_deref(), Some("from-env"));
+
+        // 配置文件中的密钥优先于环境变量
+        let mut config = telemetry(&format!("{hash}hash_key = \"from-file\"\n")).enduser;
+        config.resolve_hash_key(env(Some("from-env"))).unwrap();
+        assert_eq!(config.hash_key.as_deref(), Some("from-file"));
+
+        // raw 模式与关闭时不需要密钥
+        let mut config = telemetry(&hash.replace("hash", "raw")).enduser;
+        assert!(config.resolve_hash_key(env(None)).is_ok());
+        let mut config = telemetry(&format!("{hash}enabled = false\n")).enduser;
+        assert!(config.resolve_hash_key(env(None)).is_ok());
     }
 }
@@ -5,11 +5,15 @@ use serde::Serialize;
 
 /// 统一 API 响应格式
 /// {"code": 0, "msg": "", "data": ...}
+///
+/// 错误响应额外携带 `trace_id`，便于按客户端报错定位 trace
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T: Serialize> {
     pub code: i32,
     pub msg: String,
     pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -19,6 +23,7 @@ impl<T: Serialize> ApiResponse<T> {
             code: 0,
             msg: String::new(),
             data: Some(data),
+            trace_id: None,
         }
     }
 
@@ -35,15 +40,17 @@ impl ApiResponse<()> {
             code: 0,
             msg: String::new(),
             data: None,
+            trace_id: None,
         }
     }
 
-    /// 错误响应
+    /// 错误响应，附带当前请求的 trace_id
     pub fn error(code: i32, msg: impl Into<String>) -> Self {
         Self {
             code,
             msg: msg.into(),
             data: None,
+            trace_id: crate::telemetry::current_trace_id(),
         }
     }
 }
@@ -1,8 +1,12 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use opentelemetry::trace::Status;
+use opentelemetry::KeyValue;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::dto::response::ApiResponse;
+use crate::telemetry;
 
 /// 统一错误类型
 #[derive(Debug)]
@@ -15,6 +19,61 @@ pub enum AppError {
     AuthFailed(String),
     /// 未授权
     Unauthorized(String),
+    /// 请求体超过大小限制
+    PayloadTooLarge(String),
+    /// 请求过于频繁
+    TooManyRequests(String),
+}
+
+/// 响应扩展标记：错误已记录到 span，请求中间件据此不再用状态码覆盖 `error.type`
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorRecorded;
+
+impl AppError {
+    /// 稳定的错误类型名，记录为 span 的 `error.type`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NotFound",
+            AppError::Validation(_) => "Validation",
+            AppError::Internal(_) => "Internal",
+            AppError::Database(_) => "Database",
+            AppError::AuthFailed(_) => "AuthFailed",
+            AppError::Unauthorized(_) => "Unauthorized",
+            AppError::PayloadTooLarge(_) => "PayloadTooLarge",
+            AppError::TooManyRequests(_) => "TooManyRequests",
+        }
+    }
+
+    /// 在当前 span 上记录 `error.type` 与 `exception` 事件，5xx 同时将 span 状态置为错误
+    ///
+    /// 事件直接写入 OTel span，不经日志输出；需要日志的错误在 `into_response` 中单独记录
+    fn record_on_span(&self, status: StatusCode) {
+        let kind = self.kind();
+        let message = match self {
+            AppError::Database(err) => err.to_string(),
+            AppError::NotFound(msg)
+            | AppError::Validation(msg)
+            | AppError::Internal(msg)
+            | AppError::AuthFailed(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::TooManyRequests(msg) => msg.clone(),
+        };
+
+        let span = tracing::Span::current();
+        span.set_attribute("error.type", kind);
+        telemetry::add_span_event(
+            &span,
+            "exception",
+            vec![
+                KeyValue::new("exception.type", kind),
+                KeyValue::new("exception.message", message.clone()),
+            ],
+        );
+        if status.is_server_error() {
+            span.set_status(Status::error(message));
+        }
+    }
 }
 
 impl IntoResponse for AppError {
@@ -29,9 +88,14 @@ impl IntoResponse for AppError {
             }
             AppError::AuthFailed(msg) => (StatusCode::UNAUTHORIZED, 401, msg.clone()),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, 401, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, 413, msg.clone()),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, 429, msg.clone()),
         };
+        self.record_on_span(status);
 
-        (status, Json(ApiResponse::<()>::error(code, message))).into_response()
+        let mut response = (status, Json(ApiResponse::<()>::error(code, message))).into_response();
+        response.extensions_mut().insert(ErrorRecorded);
+        response
     }
 }
 
@@ -40,3 +104,84 @@ impl From<sea_orm::DbErr> for AppError {
         AppError::Database(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use opentelemetry::trace::{Status, TracerProvider};
+    use opentelemetry::Context;
+    use opentelemetry_sdk::error::OTelSdkResult;
+    use opentelemetry_sdk::trace::{SdkTracerProvider, Span, SpanData, SpanProcessor};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Debug, Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<SpanData>>>);
+
+    impl SpanProcessor for Collect {
+        fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.0.lock().unwrap().push(span);
+        }
+
+        fn force_flush(&self) -> OTelSdkResult {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> OTelSdkResult {
+            Ok(())
+        }
+    }
+
+    /// 在一个请求 span 内把错误转成响应，返回响应与导出的 span
+    fn respond(err: AppError) -> (Response, SpanData) {
+        let spans = Collect::default();
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(spans.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        let response = tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("request").in_scope(|| err.into_response())
+        });
+        let span = spans.0.lock().unwrap().pop().expect("request span exported");
+        (response, span)
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Option<String> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.to_string())
+    }
+
+    #[test]
+    fn test_error_recorded_on_span() {
+        let (response, span) = respond(AppError::Database(sea_orm::DbErr::Custom("boom".into())));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.extensions().get::<ErrorRecorded>().is_some());
+        assert_eq!(attribute(&span, "error.type").as_deref(), Some("Database"));
+        assert!(matches!(span.status, Status::Error { .. }));
+
+        // 另一条是 "Database error" 日志经 tracing-opentelemetry 转成的事件
+        let events: Vec<_> = span.events.iter().filter(|e| e.name == "exception").collect();
+        assert_eq!(events.len(), 1);
+        let exception_type = events[0]
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == "exception.type")
+            .map(|kv| kv.value.to_string());
+        assert_eq!(exception_type.as_deref(), Some("Database"));
+
+        // 4xx 只记录类型与事件，不把 span 标为错误
+        let (response, span) = respond(AppError::NotFound("missing".into()));
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(attribute(&span, "error.type").as_deref(), Some("NotFound"));
+        assert_eq!(span.status, Status::Unset);
+        assert_eq!(span.events.iter().map(|e| e.name.as_ref()).collect::<Vec<_>>(), ["exception"]);
+    }
+}
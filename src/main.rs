@@ -12,40 +12,75 @@ mod shutdown;
 mod telemetry;
 mod utils;
 
+use std::net::SocketAddr;
+
 use clap::Parser;
 use tokio::net::TcpListener;
 
 use crate::app::AppState;
-use crate::cli::Cli;
+use crate::cli::{Cli, Command};
 use crate::config::AppConfig;
 
+/// `jemalloc` feature 以 jemalloc 替换全局分配器，为 `/debug/pprof/heap` 提供堆样本
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+/// 启动时即开启堆采样，平均每分配 2^19 字节（512 KiB）记录一次调用栈
+#[cfg(feature = "jemalloc")]
+#[allow(non_upper_case_globals)]
+#[unsafe(export_name = "_rjem_malloc_conf")]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
     let config = AppConfig::from_file(&cli.env);
 
-    // 初始化可观测性（tracing + logging + profiling）
-    let telemetry_guard = telemetry::init_telemetry(&config.telemetry);
+    if let Some(Command::Trace { trace_id, files }) = &cli.command {
+        if let Err(e) = telemetry::trace_tree::print(trace_id, files, &config.telemetry.file_export) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    tracing::info!(
-        "Starting server with OpenTelemetry tracing, logging and Pyroscope profiling enabled"
-    );
+    // 初始化可观测性（tracing + logging + metrics + profiling），单个信号失败只会降级
+    let telemetry_guard = telemetry::init_telemetry(&config.telemetry, &cli.env);
+
+    tracing::info!("Starting server");
 
     // 初始化数据库
-    let db = db::init_db(&config.database.url)
+    let mut db = db::init_db(&config.database.url)
         .await
         .expect("Failed to initialize database");
+    telemetry::db_metrics::instrument(&mut db, &config.database);
     tracing::info!("Database initialized");
 
     // 构建应用
     let state = AppState { db };
-    let router = app::create_router(state);
+    let mut router = app::create_router(state);
+
+    // Prometheus 抓取端点：配置了独立地址则单独监听，否则挂在主 Router 上
+    if let Some(exporter) = telemetry_guard.prometheus.clone() {
+        match config.telemetry.metrics.prometheus_addr.clone() {
+            Some(addr) => {
+                tokio::spawn(exporter.serve(addr));
+            }
+            None => router = router.merge(exporter.router()),
+        }
+    }
 
     // 启动服务器
+    middleware::forwarded::init(&config.server);
     let listener = TcpListener::bind(&config.server.addr).await.unwrap();
     tracing::info!("Server listening on {}", listener.local_addr().unwrap());
 
-    axum::serve(listener, router)
+    // 注入 ConnectInfo，请求 span 记录 client.address
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
         .with_graceful_shutdown(shutdown::shutdown_signal())
         .await
         .unwrap();
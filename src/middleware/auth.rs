@@ -6,19 +6,17 @@ use axum::{
     middleware::Next,
     response::IntoResponse,
 };
+use opentelemetry::context::FutureExt;
 
 use crate::api::admin::auth::service::AuthService;
 use crate::app::AppState;
 use crate::error::AppError;
 use crate::middleware::CurrentUser;
+use crate::telemetry;
 
 /// 从 Authorization Header 解析 Token
 fn extract_token(authorization: &str) -> Option<&str> {
-    if authorization.starts_with("Bearer ") {
-        Some(&authorization[7..])
-    } else {
-        None
-    }
+    authorization.strip_prefix("Bearer ")
 }
 
 /// 认证中间件 - 验证 Token 并注入 CurrentUser
@@ -59,8 +57,15 @@ pub async fn auth_middleware(
         token: token.to_string(),
     };
 
+    // 记录到请求 span，并把 enduser.id 放入 baggage 供后续 span 与日志携带
+    let cx = telemetry::enduser::record(&current_user);
+
     // 注入到请求扩展中
     request.extensions_mut().insert(current_user);
 
-    Ok(next.run(request).await)
+    let response = match cx {
+        Some(cx) => next.run(request).with_context(cx).await,
+        None => next.run(request).await,
+    };
+    Ok(response)
 }
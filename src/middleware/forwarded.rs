@@ -0,0 +1,165 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+
+use axum::extract::{ConnectInfo, Request};
+use ipnet::IpNet;
+
+use crate::config::ServerConfig;
+
+/// 受信任的反向代理网段，未初始化时不信任任何代理
+static TRUSTED_PROXIES: OnceLock<Vec<IpNet>> = OnceLock::new();
+
+/// 解析受信任的反向代理列表，无效条目记录警告后忽略
+pub fn init(config: &ServerConfig) {
+    let proxies = config
+        .trusted_proxies
+        .iter()
+        .filter_map(|entry| match parse_net(entry) {
+            Some(net) => Some(net),
+            None => {
+                tracing::warn!(entry = %entry, "Invalid trusted proxy, ignored");
+                None
+            }
+        })
+        .collect();
+    let _ = TRUSTED_PROXIES.set(proxies);
+}
+
+/// 接受 CIDR 或单个 IP
+fn parse_net(entry: &str) -> Option<IpNet> {
+    let entry = entry.trim();
+    entry
+        .parse()
+        .ok()
+        .or_else(|| entry.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+fn trusted_proxies() -> &'static [IpNet] {
+    TRUSTED_PROXIES.get().map_or(&[], Vec::as_slice)
+}
+
+fn is_trusted(trusted: &[IpNet], ip: &IpAddr) -> bool {
+    trusted.iter().any(|net| net.contains(ip))
+}
+
+fn peer_address(request: &Request) -> Option<IpAddr> {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+/// 客户端地址：TCP 对端为受信任代理时，取 `X-Forwarded-For` 中自右向左第一个非代理地址
+pub fn client_address(request: &Request) -> Option<IpAddr> {
+    resolve_client(
+        peer_address(request),
+        header(request, "x-forwarded-for"),
+        trusted_proxies(),
+    )
+}
+
+fn resolve_client(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted: &[IpNet],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !is_trusted(trusted, &peer) {
+        return Some(peer);
+    }
+    // 左侧的地址由客户端自行填写，只有经过受信任代理追加的部分可信
+    let mut client = peer;
+    for hop in forwarded_for.into_iter().flat_map(|v| v.rsplit(',')) {
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(trusted, &ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// 请求协议：仅 `http` / `https`，`X-Forwarded-Proto` 只采信受信任代理的值
+pub fn scheme(request: &Request) -> &'static str {
+    resolve_scheme(
+        peer_address(request),
+        header(request, "x-forwarded-proto"),
+        request.uri().scheme_str(),
+        trusted_proxies(),
+    )
+}
+
+fn resolve_scheme(
+    peer: Option<IpAddr>,
+    forwarded_proto: Option<&str>,
+    uri_scheme: Option<&str>,
+    trusted: &[IpNet],
+) -> &'static str {
+    let forwarded = forwarded_proto
+        .filter(|_| peer.is_some_and(|ip| is_trusted(trusted, &ip)))
+        .and_then(|v| v.split(',').next());
+    forwarded
+        .and_then(known_scheme)
+        .or_else(|| uri_scheme.and_then(known_scheme))
+        .unwrap_or("http")
+}
+
+fn known_scheme(value: &str) -> Option<&'static str> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("https") {
+        Some("https")
+    } else if value.eq_ignore_ascii_case("http") {
+        Some("http")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_forwarded_headers_need_trusted_peer() {
+        let trusted = [
+            parse_net("10.0.0.0/8").unwrap(),
+            parse_net("127.0.0.1").unwrap(),
+        ];
+
+        // 直连客户端伪造的头被忽略
+        let peer = Some(ip("203.0.113.9"));
+        assert_eq!(resolve_client(peer, Some("1.2.3.4"), &trusted), peer);
+        assert_eq!(resolve_scheme(peer, Some("https"), None, &trusted), "http");
+
+        // 经代理转发时跳过代理链，左侧伪造的地址不被采信
+        let peer = Some(ip("127.0.0.1"));
+        assert_eq!(
+            resolve_client(peer, Some("1.2.3.4, 198.51.100.7, 10.1.2.3"), &trusted),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(resolve_client(peer, None, &trusted), peer);
+        assert_eq!(resolve_scheme(peer, Some("HTTPS"), None, &trusted), "https");
+    }
+
+    #[test]
+    fn test_scheme_is_bounded() {
+        let trusted = [parse_net("127.0.0.1").unwrap()];
+        let peer = Some(ip("127.0.0.1"));
+        assert_eq!(resolve_scheme(peer, Some("gopher"), None, &trusted), "http");
+        assert_eq!(
+            resolve_scheme(peer, Some("x"), Some("https"), &trusted),
+            "https"
+        );
+        assert_eq!(resolve_scheme(None, None, Some("ftp"), &trusted), "http");
+    }
+}
@@ -1,86 +1,269 @@
+use std::sync::Arc;
+
 use axum::body::Body;
-use axum::extract::Request;
+use axum::extract::{MatchedPath, Request};
+use axum::http::header::{HOST, USER_AGENT};
+use axum::http::uri::Authority;
+use axum::http::{HeaderName, HeaderValue};
 use axum::middleware::Next;
 use axum::response::Response;
-use http_body_util::BodyExt;
 use opentelemetry::trace::TraceContextExt;
+use opentelemetry::KeyValue;
+use opentelemetry_http::HeaderExtractor;
 use tracing::Instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use super::forwarded;
+use crate::error::ErrorRecorded;
+use crate::telemetry::body_capture::{self, CaptureBody};
+use crate::telemetry::metrics::{self, HttpServerMetrics};
+use crate::telemetry::{enduser, redaction, runtime_metrics};
+
+/// 请求 ID 头：入站值沿用，缺省时以 trace_id 填充
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+/// W3C Trace Context Level 2 响应头：`00-{trace_id}-{span_id}-{flags}`
+static TRACERESPONSE: HeaderName = HeaderName::from_static("traceresponse");
+
 /// 记录请求/响应 body、trace 上下文、响应状态与延迟的中间件
 pub async fn log_bodies(request: Request, next: Next) -> Response {
     let method = request.method().clone();
     let uri = request.uri().clone();
     let path = uri.path().to_owned();
+    // span 名、http.route 与指标都使用路由模板，避免 /products/17、/products/18 各成一类
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_owned());
+    // 未匹配到路由（404）时 span 名只用请求方法
+    let span_name = match &route {
+        Some(route) => format!("{method} {route}"),
+        None => method.to_string(),
+    };
+    let headers = request.headers();
+    let scheme = forwarded::scheme(&request);
+    let authority = server_authority(&request);
 
     // 创建 tracing span，替代原来的 TraceLayer::make_span_with
     let span: tracing::Span = tracing::info_span!(
         "http_request",
-        otel.name = %format!("{} {}", method, path),
-        http.method = %method,
-        http.uri = %uri,
-        http.route = %path,
+        otel.name = %span_name,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %method,
+        http.route = route.as_deref(),
+        http.response.status_code = tracing::field::Empty,
+        error.type = tracing::field::Empty,
+        url.scheme = %scheme,
+        url.path = %path,
+        url.query = uri.query(),
+        user_agent.original = headers.get(USER_AGENT).and_then(|v| v.to_str().ok()),
+        client.address = forwarded::client_address(&request).map(tracing::field::display),
+        server.address = authority.as_ref().map(|a| a.host()),
+        server.port = authority.as_ref().and_then(|a| a.port_u16()),
+        "http.request.header.x-request-id" = tracing::field::Empty,
+        // 由认证中间件填充
+        enduser.id = tracing::field::Empty,
+        enduser.role = tracing::field::Empty,
         trace_id = tracing::field::Empty,
         span_id = tracing::field::Empty,
     );
 
+    // 从入站 traceparent / baggage 等头提取上游上下文，作为请求 span 的父级
+    // 客户端自带的 enduser.* baggage 不可信，身份只由认证中间件写入
+    let parent_cx = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    span.set_parent(enduser::strip_inbound(parent_cx));
+
     // 进入 span 后从 OTel context 提取 trace_id / span_id
     let _enter = span.enter();
     let otel_ctx = span.context();
     let otel_span = otel_ctx.span();
     let span_context = otel_span.span_context();
-    if span_context.is_valid() {
-        span.record("trace_id", span_context.trace_id().to_string());
+    let trace_id = span_context.trace_id().to_string();
+    let trace_response = span_context.is_valid().then(|| {
+        span.record("trace_id", &trace_id);
         span.record("span_id", span_context.span_id().to_string());
-    }
+        format!(
+            "00-{trace_id}-{}-{:02x}",
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        )
+    });
     drop(_enter);
 
-    async move {
-        // 提取并记录请求 body
-        let (parts, body) = request.into_parts();
-        let bytes = body.collect().await.unwrap_or_default().to_bytes();
+    // 入站 X-Request-Id 原样沿用，缺省时以 trace_id 作为请求 ID
+    let request_id = inbound_request_id(&request).or_else(|| {
+        trace_response
+            .as_ref()
+            .and_then(|_| HeaderValue::from_str(&trace_id).ok())
+    });
+    if let Some(request_id) = request_id.as_ref().and_then(|v| v.to_str().ok()) {
+        span.record("http.request.header.x-request-id", request_id);
+    }
 
-        if !bytes.is_empty() {
-            let body_str = String::from_utf8_lossy(&bytes);
-            tracing::debug!(
-                http.method = %method,
-                http.uri = %uri,
-                request.body = %body_str,
-                "request body"
-            );
-        }
+    let http_metrics = metrics::http_server();
+    let mut attributes = vec![
+        KeyValue::new("http.request.method", method.to_string()),
+        KeyValue::new("url.scheme", scheme),
+    ];
+    let active_guard = ActiveRequestGuard::new(http_metrics.clone(), attributes.clone());
+
+    let request = async move {
+        // 按路由、抽样与 content-type 决定是否记录 body；body 始终流式透传，只保留前 max_bytes 字节
+        let capture = body_capture::policy();
+        let route_enabled = capture.route_enabled(route.as_deref().unwrap_or(&path));
+        let sampled = route_enabled && capture.sample();
+        let request_limit = if route_enabled && capture.content_type_allowed(request.headers()) {
+            capture.max_bytes()
+        } else {
+            0
+        };
+        let request_headers = route_enabled.then(|| request.headers().clone());
 
-        let request = Request::from_parts(parts, Body::from(bytes));
+        let (parts, body) = request.into_parts();
+        let (body, request_capture) = CaptureBody::new(body, request_limit);
+        let request = Request::from_parts(parts, Body::new(body));
 
         // 调用下游处理并计时
         let start = std::time::Instant::now();
-        let response = next.run(request).await;
+        let mut response = next.run(request).await;
         let latency = start.elapsed();
+        let status = response.status().as_u16();
 
-        // 提取并记录响应 body
-        let (parts, body) = response.into_parts();
-        let bytes = body.collect().await.unwrap_or_default().to_bytes();
+        // 回传 trace 上下文与请求 ID，客户端报错时可据此找到对应 trace
+        let headers = response.headers_mut();
+        if let Some(value) = trace_response.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            headers.insert(TRACERESPONSE.clone(), value);
+        }
+        if let Some(request_id) = request_id {
+            headers.insert(X_REQUEST_ID.clone(), request_id);
+        }
 
-        if !bytes.is_empty() {
-            let body_str = String::from_utf8_lossy(&bytes);
+        // 5xx 标记为错误 span；4xx 属于客户端问题，按语义约定不设置 span 状态
+        // AppError 已自行记录状态与 error.type 的响应不再覆盖
+        let span = tracing::Span::current();
+        span.record("http.response.status_code", status);
+        if response.status().is_server_error()
+            && response.extensions().get::<ErrorRecorded>().is_none()
+        {
+            span.record("otel.status_code", "ERROR");
+            span.record("error.type", status.to_string());
+        }
+
+        drop(active_guard);
+        if let Some(route) = route {
+            attributes.push(KeyValue::new("http.route", route));
+        }
+        attributes.push(KeyValue::new(
+            "http.response.status_code",
+            i64::from(status),
+        ));
+        http_metrics
+            .request_duration
+            .record(latency.as_secs_f64(), &attributes);
+
+        let request_captured = request_capture.take();
+        http_metrics
+            .request_body_size
+            .record(request_captured.total, &attributes);
+
+        // 请求头与 body 按脱敏规则处理后再记录
+        let redactor = redaction::redactor();
+        let log_bodies = sampled || (route_enabled && capture.capture_on_status(status));
+        if log_bodies {
+            let request_body = (!request_captured.bytes.is_empty())
+                .then(|| redactor.redact_body(&request_captured.bytes));
             tracing::debug!(
                 http.method = %method,
                 http.uri = %uri,
-                status = %parts.status.as_u16(),
-                latency_ms = %latency.as_millis(),
-                response.body = %body_str,
-                "response body"
+                request.headers = request_headers.map(|h| redactor.redact_headers(&h)),
+                request.body = request_body,
+                request.body.size = request_captured.total,
+                request.body.truncated = request_captured.truncated,
+                "request body"
             );
+        }
+
+        // 响应 body 在发送完毕（或连接断开）时记录
+        let response_limit = if log_bodies && capture.content_type_allowed(response.headers()) {
+            capture.max_bytes()
         } else {
-            tracing::debug!(
-                status = %parts.status.as_u16(),
-                latency_ms = %latency.as_millis(),
-                "response body"
-            );
+            0
+        };
+        let (parts, body) = response.into_parts();
+        let (body, _) = CaptureBody::new(body, response_limit);
+        let body = body.on_end(move |captured| {
+            http_metrics
+                .response_body_size
+                .record(captured.total, &attributes);
+            if !log_bodies {
+                return;
+            }
+            let response_body =
+                (!captured.bytes.is_empty()).then(|| redactor.redact_body(&captured.bytes));
+            span.in_scope(|| {
+                tracing::debug!(
+                    http.method = %method,
+                    http.uri = %uri,
+                    status = %status,
+                    latency_ms = %latency.as_millis(),
+                    response.body = response_body,
+                    response.body.size = captured.total,
+                    response.body.truncated = captured.truncated,
+                    "response body"
+                );
+            });
+        });
+
+        Response::from_parts(parts, Body::new(body))
+    }
+    .instrument(span);
+
+    // 统计请求任务的轮询耗时与调度延迟，反映 worker 线程是否被阻塞
+    runtime_metrics::task_monitor().instrument(request).await
+}
+
+/// 入站 `X-Request-Id`：仅接受 1~128 个可见 ASCII 字符，避免把任意内容写入日志与响应头
+fn inbound_request_id(request: &Request) -> Option<HeaderValue> {
+    request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .filter(|v| {
+            let bytes = v.as_bytes();
+            (1..=128).contains(&bytes.len()) && bytes.iter().all(u8::is_ascii_graphic)
+        })
+        .cloned()
+}
+
+/// 服务端地址：HTTP/1 取 `Host` 头，HTTP/2 取 `:authority`
+fn server_authority(request: &Request) -> Option<Authority> {
+    request
+        .headers()
+        .get(HOST)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or_else(|| request.uri().authority().cloned())
+}
+
+/// 活跃请求计数守卫：请求结束或被取消（客户端断开）时都会减一
+struct ActiveRequestGuard {
+    metrics: Arc<HttpServerMetrics>,
+    attributes: Vec<KeyValue>,
+}
+
+impl ActiveRequestGuard {
+    fn new(metrics: Arc<HttpServerMetrics>, attributes: Vec<KeyValue>) -> Self {
+        metrics.active_requests.add(1, &attributes);
+        Self {
+            metrics,
+            attributes,
         }
+    }
+}
 
-        Response::from_parts(parts, Body::from(bytes))
+impl Drop for ActiveRequestGuard {
+    fn drop(&mut self) {
+        self.metrics.active_requests.add(-1, &self.attributes);
     }
-    .instrument(span)
-    .await
 }
@@ -1,6 +1,8 @@
 pub mod auth;
 pub mod current_user;
+pub mod forwarded;
 mod log_bodies;
 
 pub use current_user::CurrentUser;
+pub use forwarded::client_address;
 pub use log_bodies::log_bodies;
@@ -11,6 +11,7 @@ impl CategoryRepository {
     }
 
     /// 按类型查询分类
+    #[allow(dead_code)]
     pub async fn find_by_type(
         db: &DatabaseConnection,
         category_type: &str,
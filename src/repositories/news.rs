@@ -7,6 +7,7 @@ pub struct NewsRepository;
 
 impl NewsRepository {
     /// 查询全部新闻
+    #[allow(dead_code)]
     pub async fn find_all(db: &DatabaseConnection) -> Result<Vec<news::Model>, DbErr> {
         news::Entity::find().all(db).await
     }
@@ -50,6 +51,7 @@ impl NewsRepository {
     }
 
     /// 创建新闻
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         db: &DatabaseConnection,
         title: String,
@@ -87,6 +89,7 @@ impl NewsRepository {
     }
 
     /// 更新新闻
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         db: &DatabaseConnection,
         mut model: news::ActiveModel,
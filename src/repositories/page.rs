@@ -64,6 +64,7 @@ impl PageRepository {
     }
 
     /// 更新页面
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         db: &DatabaseConnection,
         mut model: page::ActiveModel,
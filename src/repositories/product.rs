@@ -7,6 +7,7 @@ pub struct ProductRepository;
 
 impl ProductRepository {
     /// 查询全部产品
+    #[allow(dead_code)]
     pub async fn find_all(db: &DatabaseConnection) -> Result<Vec<product::Model>, DbErr> {
         product::Entity::find().all(db).await
     }
@@ -52,6 +53,7 @@ impl ProductRepository {
     }
 
     /// 创建产品
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         db: &DatabaseConnection,
         name: String,
@@ -82,6 +84,7 @@ impl ProductRepository {
     }
 
     /// 更新产品
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         db: &DatabaseConnection,
         mut model: product::ActiveModel,
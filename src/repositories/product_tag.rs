@@ -26,7 +26,6 @@ impl ProductTagRepository {
         let model = product_tag::ActiveModel {
             product_id: Set(product_id),
             tag_id: Set(tag_id),
-            ..Default::default()
         };
         model.insert(db).await
     }
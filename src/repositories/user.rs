@@ -2,8 +2,10 @@ use sea_orm::*;
 
 use crate::models::user;
 
+#[allow(dead_code)]
 pub struct UserRepository;
 
+#[allow(dead_code)]
 impl UserRepository {
     /// 查询全部用户
     pub async fn find_all(db: &DatabaseConnection) -> Result<Vec<user::Model>, DbErr> {
@@ -0,0 +1,232 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, ready};
+
+use axum::body::{Body, Bytes};
+use axum::http::HeaderMap;
+use axum::http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use http_body::{Frame, SizeHint};
+use rand::RngExt;
+
+use super::sampling::route_matches;
+use crate::config::BodyCaptureConfig;
+
+static POLICY: OnceLock<BodyCapturePolicy> = OnceLock::new();
+
+/// 按配置初始化 body 记录策略
+pub fn init(config: &BodyCaptureConfig) {
+    let _ = POLICY.set(BodyCapturePolicy::new(config.clone()));
+}
+
+/// 获取 body 记录策略，未初始化时使用默认配置
+pub fn policy() -> &'static BodyCapturePolicy {
+    POLICY.get_or_init(|| BodyCapturePolicy::new(BodyCaptureConfig::default()))
+}
+
+/// 决定某个请求是否记录 body、最多记录多少字节
+#[derive(Debug)]
+pub struct BodyCapturePolicy {
+    config: BodyCaptureConfig,
+}
+
+impl BodyCapturePolicy {
+    pub fn new(mut config: BodyCaptureConfig) -> Self {
+        for prefix in &mut config.skip_content_types {
+            *prefix = prefix.to_ascii_lowercase();
+        }
+        Self { config }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.config.max_bytes
+    }
+
+    /// 全局开关与路由规则，按顺序匹配第一条
+    pub fn route_enabled(&self, route: &str) -> bool {
+        self.config.enabled
+            && self
+                .config
+                .rules
+                .iter()
+                .find(|rule| route_matches(&rule.route, route))
+                .is_none_or(|rule| rule.enabled)
+    }
+
+    /// 按比例抽样，决定请求是否无条件记录 body
+    pub fn sample(&self) -> bool {
+        let ratio = self.config.sample_ratio;
+        ratio >= 1.0 || (ratio > 0.0 && rand::rng().random_bool(ratio))
+    }
+
+    /// 未抽中的请求是否因响应状态被补记
+    pub fn capture_on_status(&self, status: u16) -> bool {
+        self.config.always_on_server_error && status >= 500
+    }
+
+    /// multipart、二进制及压缩过的 body 不记录
+    pub fn content_type_allowed(&self, headers: &HeaderMap) -> bool {
+        let encoded = headers
+            .get(CONTENT_ENCODING)
+            .is_some_and(|v| v.as_bytes() != b"identity");
+        if encoded {
+            return false;
+        }
+
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        !self
+            .config
+            .skip_content_types
+            .iter()
+            .any(|prefix| content_type.starts_with(prefix.as_str()))
+    }
+}
+
+/// 流经 `CaptureBody` 的数据统计：总字节数与前 `limit` 字节的副本
+#[derive(Debug, Default)]
+pub struct Captured {
+    pub bytes: Vec<u8>,
+    pub total: u64,
+    pub truncated: bool,
+}
+
+/// 透传式 body 包装：数据帧原样转发，同时保留前 `limit` 字节用于日志
+///
+/// `limit` 为 0 时只统计大小，不复制数据
+pub struct CaptureBody {
+    inner: Body,
+    limit: usize,
+    captured: Arc<Mutex<Captured>>,
+    on_end: Option<Box<dyn FnOnce(Captured) + Send>>,
+}
+
+/// 读取请求 body 捕获结果的句柄
+pub struct CaptureHandle(Arc<Mutex<Captured>>);
+
+impl CaptureHandle {
+    /// 取出当前已捕获的内容（下游可能没有读完 body）
+    pub fn take(&self) -> Captured {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl CaptureBody {
+    pub fn new(inner: Body, limit: usize) -> (Self, CaptureHandle) {
+        let captured = Arc::new(Mutex::new(Captured::default()));
+        let body = Self {
+            inner,
+            limit,
+            captured: captured.clone(),
+            on_end: None,
+        };
+        (body, CaptureHandle(captured))
+    }
+
+    /// body 读完或被丢弃（客户端断开）时回调一次
+    pub fn on_end(mut self, f: impl FnOnce(Captured) + Send + 'static) -> Self {
+        self.on_end = Some(Box::new(f));
+        self
+    }
+
+    fn record(&self, data: &Bytes) {
+        let mut captured = self.captured.lock().unwrap_or_else(|e| e.into_inner());
+        captured.total += data.len() as u64;
+        let room = self.limit.saturating_sub(captured.bytes.len());
+        if data.len() > room {
+            captured.truncated = true;
+        }
+        captured
+            .bytes
+            .extend_from_slice(&data[..room.min(data.len())]);
+    }
+}
+
+impl http_body::Body for CaptureBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        if let Some(Ok(frame)) = &frame
+            && let Some(data) = frame.data_ref()
+        {
+            self.record(data);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for CaptureBody {
+    fn drop(&mut self) {
+        if let Some(on_end) = self.on_end.take() {
+            on_end(CaptureHandle(self.captured.clone()).take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use http_body_util::BodyExt;
+
+    use super::*;
+    use crate::config::BodyCaptureRule;
+
+    #[tokio::test]
+    async fn test_capture_is_bounded_and_passthrough() {
+        let (body, handle) = CaptureBody::new(Body::from("0123456789"), 4);
+        let forwarded = body.collect().await.unwrap().to_bytes();
+        assert_eq!(forwarded, "0123456789");
+
+        let captured = handle.take();
+        assert_eq!(captured.bytes, b"0123");
+        assert_eq!(captured.total, 10);
+        assert!(captured.truncated);
+    }
+
+    #[test]
+    fn test_policy_rules_and_content_types() {
+        let policy = BodyCapturePolicy::new(BodyCaptureConfig {
+            rules: vec![
+                BodyCaptureRule {
+                    route: "/api/admin/auth/login".into(),
+                    enabled: true,
+                },
+                BodyCaptureRule {
+                    route: "/api/admin/*".into(),
+                    enabled: false,
+                },
+            ],
+            ..BodyCaptureConfig::default()
+        });
+        assert!(policy.route_enabled("/api/admin/auth/login"));
+        assert!(!policy.route_enabled("/api/admin/products/{id}"));
+        assert!(policy.route_enabled("/api/common/inquiry"));
+
+        let mut headers = HeaderMap::new();
+        assert!(policy.content_type_allowed(&headers));
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=x"),
+        );
+        assert!(!policy.content_type_allowed(&headers));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert!(policy.content_type_allowed(&headers));
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        assert!(!policy.content_type_allowed(&headers));
+    }
+}
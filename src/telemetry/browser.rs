@@ -0,0 +1,684 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+use opentelemetry::trace::{
+    Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry::{Array, InstrumentationScope, Key, KeyValue, StringValue, Value};
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1 as proto;
+use opentelemetry_proto::tonic::common::v1::any_value::Value as ProtoValue;
+use opentelemetry_proto::tonic::logs::v1::LogRecord as ProtoLogRecord;
+use opentelemetry_proto::tonic::resource::v1::Resource as ProtoResource;
+use opentelemetry_proto::tonic::trace::v1::span::{self, SpanKind as ProtoSpanKind};
+use opentelemetry_proto::tonic::trace::v1::status::StatusCode;
+use opentelemetry_proto::tonic::trace::v1::{Span as ProtoSpan, Status as ProtoStatus};
+use opentelemetry_sdk::logs::{SdkLogger, SdkLoggerProvider};
+use opentelemetry_sdk::trace::{SpanData, SpanEvents, SpanLinks, SpanProcessor};
+use serde::Serialize;
+
+use super::file_export::{FileLogExporter, FileSpanExporter};
+use super::{logger, otlp, resource, tracer};
+use crate::config::{BrowserTelemetryConfig, TelemetryConfig};
+use crate::utils::rate_limit::RateLimiter;
+
+/// 按日志级别数值（1 ~ 24）排列
+const SEVERITIES: [Severity; 24] = [
+    Severity::Trace,
+    Severity::Trace2,
+    Severity::Trace3,
+    Severity::Trace4,
+    Severity::Debug,
+    Severity::Debug2,
+    Severity::Debug3,
+    Severity::Debug4,
+    Severity::Info,
+    Severity::Info2,
+    Severity::Info3,
+    Severity::Info4,
+    Severity::Warn,
+    Severity::Warn2,
+    Severity::Warn3,
+    Severity::Warn4,
+    Severity::Error,
+    Severity::Error2,
+    Severity::Error3,
+    Severity::Error4,
+    Severity::Fatal,
+    Severity::Fatal2,
+    Severity::Fatal3,
+    Severity::Fatal4,
+];
+
+static PIPELINE: OnceLock<Pipeline> = OnceLock::new();
+
+/// 单次上报的处理结果
+#[derive(Debug, Default, Serialize)]
+pub struct Ingested {
+    /// 已转发的 span / 日志条数
+    pub accepted: usize,
+    /// 因 ID 无效或超出条数上限而丢弃的条数
+    pub rejected: usize,
+}
+
+/// 浏览器端 traces 与日志的转发管道
+pub struct Pipeline {
+    max_body_bytes: usize,
+    max_items: usize,
+    limiter: RateLimiter,
+    filter: AttributeFilter,
+    span_processors: Vec<Box<dyn SpanProcessor>>,
+    logger_provider: Option<SdkLoggerProvider>,
+}
+
+/// 未启用时返回 None，接收接口据此返回 404
+pub fn pipeline() -> Option<&'static Pipeline> {
+    PIPELINE.get()
+}
+
+/// 创建浏览器端导出管道，复用服务端的 OTLP 与文件导出配置，返回初始化失败的原因
+///
+/// 文件导出与服务端写入同一文件，以独立的 Resource 区分
+pub(super) fn init(
+    config: &TelemetryConfig,
+    environment: &str,
+    file_spans: Option<&FileSpanExporter>,
+    file_logs: Option<&FileLogExporter>,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    if !config.browser.enabled {
+        return failures;
+    }
+    let resource = resource::browser(&config.browser, &config.service, environment);
+
+    let otlp_span_exporter = (config.otel_enabled && config.traces.enabled)
+        .then(|| otlp::span_exporter(config))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("浏览器端 traces 初始化失败，已停用: {e}")))
+                .ok()
+        });
+    let span_processors = tracer::browser_processors(
+        &resource,
+        config,
+        otlp_span_exporter,
+        file_spans.map(FileSpanExporter::share),
+    );
+
+    let otlp_log_exporter = (config.otel_enabled && config.logs.enabled)
+        .then(|| otlp::log_exporter(config))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("浏览器端 logs 初始化失败，已停用: {e}")))
+                .ok()
+        });
+    let logger_provider = logger::init_browser_logger(
+        resource,
+        config,
+        otlp_log_exporter,
+        file_logs.map(FileLogExporter::share),
+    );
+
+    let _ = PIPELINE.set(Pipeline::new(
+        &config.browser,
+        span_processors,
+        logger_provider,
+    ));
+    failures
+}
+
+/// 写完批处理队列中的浏览器端数据
+pub fn shutdown() {
+    let Some(pipeline) = PIPELINE.get() else {
+        return;
+    };
+    for processor in &pipeline.span_processors {
+        if let Err(e) = processor.shutdown() {
+            eprintln!("Failed to shutdown browser span processor: {:?}", e);
+        }
+    }
+    if let Some(provider) = &pipeline.logger_provider
+        && let Err(e) = provider.shutdown()
+    {
+        eprintln!("Failed to shutdown browser logger provider: {:?}", e);
+    }
+}
+
+impl Pipeline {
+    fn new(
+        config: &BrowserTelemetryConfig,
+        span_processors: Vec<Box<dyn SpanProcessor>>,
+        logger_provider: Option<SdkLoggerProvider>,
+    ) -> Self {
+        Self {
+            max_body_bytes: config.max_body_bytes,
+            max_items: config.max_items,
+            limiter: RateLimiter::per_minute(config.rate_limit_per_minute, config.rate_limit_burst),
+            filter: AttributeFilter::new(config),
+            span_processors,
+            logger_provider,
+        }
+    }
+
+    /// 单次请求 body 上限（字节）
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// 按客户端限流，被限流时返回需等待的时间
+    pub fn check_rate(&self, client: &str) -> Result<(), Duration> {
+        self.limiter.check(client)
+    }
+
+    /// 过滤属性后把 spans 交给导出处理器
+    pub fn ingest_traces(&self, request: ExportTraceServiceRequest) -> Ingested {
+        let mut result = Ingested::default();
+        for resource_spans in request.resource_spans {
+            let extra = self.filter.retain(resource_spans.resource);
+            for scope_spans in resource_spans.scope_spans {
+                let scope = scope(scope_spans.scope.unwrap_or_default());
+                for span in scope_spans.spans {
+                    let data = (result.accepted < self.max_items)
+                        .then(|| self.span_data(span, &scope, &extra))
+                        .flatten();
+                    let Some(data) = data else {
+                        result.rejected += 1;
+                        continue;
+                    };
+                    for processor in &self.span_processors {
+                        processor.on_end(data.clone());
+                    }
+                    result.accepted += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// 过滤属性后写入浏览器端 Logger Provider
+    pub fn ingest_logs(&self, request: ExportLogsServiceRequest) -> Ingested {
+        let mut result = Ingested::default();
+        for resource_logs in request.resource_logs {
+            let extra = self.filter.retain(resource_logs.resource);
+            for scope_logs in resource_logs.scope_logs {
+                let logger = self.logger_provider.as_ref().map(|provider| {
+                    provider.logger_with_scope(scope(scope_logs.scope.unwrap_or_default()))
+                });
+                for record in scope_logs.log_records {
+                    if result.accepted >= self.max_items {
+                        result.rejected += 1;
+                        continue;
+                    }
+                    if let Some(logger) = &logger {
+                        self.emit(logger, record, &extra);
+                    }
+                    result.accepted += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// trace_id / span_id 无效时返回 None；客户端已做过采样，一律标记为采样
+    fn span_data(
+        &self,
+        span: ProtoSpan,
+        scope: &InstrumentationScope,
+        extra: &[proto::KeyValue],
+    ) -> Option<SpanData> {
+        let span_context =
+            span_context(&span.trace_id, &span.span_id, span.flags, &span.trace_state)?;
+        let span_context = SpanContext::new(
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags() | TraceFlags::SAMPLED,
+            false,
+            span_context.trace_state().clone(),
+        );
+        let parent_span_id = if span.parent_span_id.is_empty() {
+            SpanId::INVALID
+        } else {
+            SpanId::from_bytes(span.parent_span_id.as_slice().try_into().ok()?)
+        };
+
+        let (attributes, dropped) = self.filter.attributes(with_extra(span.attributes, extra));
+        let start_time = timestamp(span.start_time_unix_nano);
+        let end_time = timestamp(span.end_time_unix_nano).max(start_time);
+
+        let mut events = SpanEvents::default();
+        events.events = span
+            .events
+            .into_iter()
+            .map(|event| self.event(event))
+            .collect();
+        events.dropped_count = span.dropped_events_count;
+        let mut links = SpanLinks::default();
+        links.links = span
+            .links
+            .into_iter()
+            .filter_map(|link| self.link(link))
+            .collect();
+        links.dropped_count = span.dropped_links_count;
+
+        Some(SpanData {
+            span_context,
+            parent_span_id,
+            span_kind: span_kind(span.kind),
+            name: Cow::Owned(truncate(span.name, self.filter.max_length)),
+            start_time,
+            end_time,
+            attributes,
+            dropped_attributes_count: span.dropped_attributes_count.saturating_add(dropped),
+            events,
+            links,
+            status: self.status(span.status),
+            instrumentation_scope: scope.clone(),
+        })
+    }
+
+    fn event(&self, event: span::Event) -> Event {
+        let (attributes, dropped) = self.filter.attributes(event.attributes);
+        Event::new(
+            truncate(event.name, self.filter.max_length),
+            timestamp(event.time_unix_nano),
+            attributes,
+            event.dropped_attributes_count.saturating_add(dropped),
+        )
+    }
+
+    fn link(&self, link: span::Link) -> Option<Link> {
+        let mut span_context =
+            span_context(&link.trace_id, &link.span_id, link.flags, &link.trace_state)?;
+        span_context = SpanContext::new(
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags(),
+            true,
+            span_context.trace_state().clone(),
+        );
+        let (attributes, dropped) = self.filter.attributes(link.attributes);
+        Some(Link::new(
+            span_context,
+            attributes,
+            link.dropped_attributes_count.saturating_add(dropped),
+        ))
+    }
+
+    fn status(&self, status: Option<ProtoStatus>) -> Status {
+        match status {
+            Some(s) if s.code == StatusCode::Error as i32 => {
+                Status::error(truncate(s.message, self.filter.max_length))
+            }
+            Some(s) if s.code == StatusCode::Ok as i32 => Status::Ok,
+            _ => Status::Unset,
+        }
+    }
+
+    fn emit(&self, logger: &SdkLogger, record: ProtoLogRecord, extra: &[proto::KeyValue]) {
+        let mut log = logger.create_log_record();
+        if record.time_unix_nano > 0 {
+            log.set_timestamp(timestamp(record.time_unix_nano));
+        }
+        log.set_observed_timestamp(if record.observed_time_unix_nano > 0 {
+            timestamp(record.observed_time_unix_nano)
+        } else {
+            SystemTime::now()
+        });
+        // 级别文本须为 'static，按级别数值取标准名称
+        if let Some(severity) = usize::try_from(record.severity_number)
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| SEVERITIES.get(i))
+        {
+            log.set_severity_number(*severity);
+            log.set_severity_text(severity.name());
+        }
+        // body 是日志正文而非属性：不截断、不按白名单过滤，整体大小已由请求 body 上限约束
+        if let Some(body) = record
+            .body
+            .and_then(|body| log_value(body, "", usize::MAX, &|_| true))
+        {
+            log.set_body(body);
+        }
+        let (attributes, _) = self
+            .filter
+            .log_attributes(with_extra(record.attributes, extra));
+        log.add_attributes(attributes);
+
+        if let Some(span_context) =
+            span_context(&record.trace_id, &record.span_id, record.flags, "")
+        {
+            log.set_trace_context(
+                span_context.trace_id(),
+                span_context.span_id(),
+                Some(span_context.trace_flags()),
+            );
+        }
+        logger.emit(log);
+    }
+}
+
+/// 按名称白名单过滤属性并截断过长的字符串
+#[derive(Debug)]
+struct AttributeFilter {
+    /// 以 `*` 结尾的按前缀匹配
+    allowed: Vec<String>,
+    max_length: usize,
+}
+
+impl AttributeFilter {
+    fn new(config: &BrowserTelemetryConfig) -> Self {
+        Self {
+            allowed: config.allowed_attributes.clone(),
+            max_length: config.max_attribute_length,
+        }
+    }
+
+    fn is_allowed(&self, key: &str) -> bool {
+        self.allowed
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == pattern,
+            })
+    }
+
+    /// 只保留允许的属性，用于客户端上报的资源属性
+    fn retain(&self, resource: Option<ProtoResource>) -> Vec<proto::KeyValue> {
+        resource
+            .map(|resource| resource.attributes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|kv| self.is_allowed(&kv.key))
+            .collect()
+    }
+
+    /// span 属性，返回保留的属性与丢弃的条数
+    fn attributes(&self, attributes: Vec<proto::KeyValue>) -> (Vec<KeyValue>, u32) {
+        let (attributes, dropped) = self.convert(attributes, |_, v| value(v, self.max_length));
+        let attributes = attributes
+            .into_iter()
+            .map(|(key, value)| KeyValue::new(key, value))
+            .collect();
+        (attributes, dropped)
+    }
+
+    /// 日志属性，允许嵌套的数组与键值对；嵌套键值对的键按 `父键.子键` 同样过滤
+    fn log_attributes(&self, attributes: Vec<proto::KeyValue>) -> (Vec<(Key, AnyValue)>, u32) {
+        self.convert(attributes, |key, v| {
+            log_value(v, key, self.max_length, &|path| self.is_allowed(path))
+        })
+    }
+
+    fn convert<T>(
+        &self,
+        attributes: Vec<proto::KeyValue>,
+        convert: impl Fn(&str, proto::AnyValue) -> Option<T>,
+    ) -> (Vec<(Key, T)>, u32) {
+        let mut dropped = 0u32;
+        let mut retained = Vec::with_capacity(attributes.len());
+        for kv in attributes {
+            match kv
+                .value
+                .filter(|_| self.is_allowed(&kv.key))
+                .and_then(|value| convert(&kv.key, value))
+            {
+                Some(value) => retained.push((Key::new(kv.key), value)),
+                None => dropped = dropped.saturating_add(1),
+            }
+        }
+        (retained, dropped)
+    }
+}
+
+/// 补上条目中没有的资源属性；浏览器端 Resource 由服务端统一设置，客户端资源属性以条目属性保留
+fn with_extra(
+    mut attributes: Vec<proto::KeyValue>,
+    extra: &[proto::KeyValue],
+) -> Vec<proto::KeyValue> {
+    for kv in extra {
+        if !attributes.iter().any(|existing| existing.key == kv.key) {
+            attributes.push(kv.clone());
+        }
+    }
+    attributes
+}
+
+fn scope(scope: proto::InstrumentationScope) -> InstrumentationScope {
+    let builder = InstrumentationScope::builder(scope.name);
+    if scope.version.is_empty() {
+        builder.build()
+    } else {
+        builder.with_version(scope.version).build()
+    }
+}
+
+/// trace_id / span_id 长度不对或全零时返回 None
+fn span_context(
+    trace_id: &[u8],
+    span_id: &[u8],
+    flags: u32,
+    trace_state: &str,
+) -> Option<SpanContext> {
+    let trace_id = TraceId::from_bytes(trace_id.try_into().ok()?);
+    let span_id = SpanId::from_bytes(span_id.try_into().ok()?);
+    if trace_id == TraceId::INVALID || span_id == SpanId::INVALID {
+        return None;
+    }
+    Some(SpanContext::new(
+        trace_id,
+        span_id,
+        // 低 8 位为 W3C trace flags
+        TraceFlags::new((flags & 0xff) as u8),
+        false,
+        TraceState::from_str(trace_state).unwrap_or_default(),
+    ))
+}
+
+fn span_kind(kind: i32) -> SpanKind {
+    match ProtoSpanKind::try_from(kind) {
+        Ok(ProtoSpanKind::Server) => SpanKind::Server,
+        Ok(ProtoSpanKind::Client) => SpanKind::Client,
+        Ok(ProtoSpanKind::Producer) => SpanKind::Producer,
+        Ok(ProtoSpanKind::Consumer) => SpanKind::Consumer,
+        _ => SpanKind::Internal,
+    }
+}
+
+fn timestamp(unix_nano: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(unix_nano)
+}
+
+fn truncate(mut value: String, max_length: usize) -> String {
+    if let Some((index, _)) = value.char_indices().nth(max_length) {
+        value.truncate(index);
+    }
+    value
+}
+
+/// span 属性只支持标量与同类型数组，其余类型丢弃
+fn value(value: proto::AnyValue, max_length: usize) -> Option<Value> {
+    Some(match value.value? {
+        ProtoValue::StringValue(s) => Value::String(truncate(s, max_length).into()),
+        ProtoValue::BoolValue(b) => Value::Bool(b),
+        ProtoValue::IntValue(i) => Value::I64(i),
+        ProtoValue::DoubleValue(d) => Value::F64(d),
+        ProtoValue::ArrayValue(array) => {
+            let values: Vec<ProtoValue> =
+                array.values.into_iter().filter_map(|v| v.value).collect();
+            Value::Array(match values.first()? {
+                ProtoValue::StringValue(_) => Array::String(collect(values, |v| match v {
+                    ProtoValue::StringValue(s) => Some(StringValue::from(truncate(s, max_length))),
+                    _ => None,
+                })?),
+                ProtoValue::BoolValue(_) => Array::Bool(collect(values, |v| match v {
+                    ProtoValue::BoolValue(b) => Some(b),
+                    _ => None,
+                })?),
+                ProtoValue::IntValue(_) => Array::I64(collect(values, |v| match v {
+                    ProtoValue::IntValue(i) => Some(i),
+                    _ => None,
+                })?),
+                ProtoValue::DoubleValue(_) => Array::F64(collect(values, |v| match v {
+                    ProtoValue::DoubleValue(d) => Some(d),
+                    _ => None,
+                })?),
+                _ => return None,
+            })
+        }
+        ProtoValue::KvlistValue(_) | ProtoValue::BytesValue(_) => return None,
+    })
+}
+
+/// 元素类型不一致时返回 None
+fn collect<T>(
+    values: Vec<ProtoValue>,
+    convert: impl Fn(ProtoValue) -> Option<T>,
+) -> Option<Vec<T>> {
+    values.into_iter().map(convert).collect()
+}
+
+/// `path` 为值所在的完整键，嵌套键值对的子键以 `path.key` 交给 `allow` 判断
+fn log_value(
+    value: proto::AnyValue,
+    path: &str,
+    max_length: usize,
+    allow: &dyn Fn(&str) -> bool,
+) -> Option<AnyValue> {
+    Some(match value.value? {
+        ProtoValue::StringValue(s) => AnyValue::from(truncate(s, max_length)),
+        ProtoValue::BoolValue(b) => AnyValue::Boolean(b),
+        ProtoValue::IntValue(i) => AnyValue::Int(i),
+        ProtoValue::DoubleValue(d) => AnyValue::Double(d),
+        ProtoValue::BytesValue(bytes) => AnyValue::Bytes(Box::new(bytes)),
+        ProtoValue::ArrayValue(array) => AnyValue::ListAny(Box::new(
+            array
+                .values
+                .into_iter()
+                .filter_map(|v| log_value(v, path, max_length, allow))
+                .collect(),
+        )),
+        ProtoValue::KvlistValue(list) => AnyValue::Map(Box::new(
+            list.values
+                .into_iter()
+                .filter_map(|kv| {
+                    let path = if path.is_empty() {
+                        kv.key.clone()
+                    } else {
+                        format!("{path}.{}", kv.key)
+                    };
+                    if !allow(&path) {
+                        return None;
+                    }
+                    Some((Key::new(kv.key), log_value(kv.value?, &path, max_length, allow)?))
+                })
+                .collect::<HashMap<_, _>>(),
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(key: &str, value: &str) -> proto::KeyValue {
+        proto::KeyValue {
+            key: key.into(),
+            value: Some(proto::AnyValue {
+                value: Some(ProtoValue::StringValue(value.into())),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_span_attributes_are_filtered_and_truncated() {
+        let pipeline = Pipeline::new(
+            &BrowserTelemetryConfig {
+                allowed_attributes: vec!["http.*".into(), "browser.*".into(), "page.url".into()],
+                max_attribute_length: 4,
+                ..BrowserTelemetryConfig::default()
+            },
+            Vec::new(),
+            None,
+        );
+        let span = ProtoSpan {
+            trace_id: vec![1; 16],
+            span_id: vec![2; 8],
+            name: "click".into(),
+            kind: ProtoSpanKind::Client as i32,
+            start_time_unix_nano: 2_000,
+            end_time_unix_nano: 1_000,
+            attributes: vec![
+                string("http.request.method", "GET"),
+                string("page.url", "https://example.com"),
+                string("user.email", "a@example.com"),
+            ],
+            ..ProtoSpan::default()
+        };
+        let extra = [string("browser.brands", "Chrome"), string("page.url", "/")];
+
+        let data = pipeline
+            .span_data(span, &scope(Default::default()), &extra)
+            .unwrap();
+        assert!(data.span_context.is_sampled());
+        assert_eq!(data.span_kind, SpanKind::Client);
+        assert_eq!(data.end_time, data.start_time);
+        assert_eq!(data.dropped_attributes_count, 1);
+        assert_eq!(
+            data.attributes,
+            vec![
+                KeyValue::new("http.request.method", "GET"),
+                KeyValue::new("page.url", "http"),
+                KeyValue::new("browser.brands", "Chro"),
+            ]
+        );
+
+        let invalid = ProtoSpan {
+            trace_id: vec![0; 16],
+            span_id: vec![2; 8],
+            ..ProtoSpan::default()
+        };
+        assert!(
+            pipeline
+                .span_data(invalid, &scope(Default::default()), &[])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_nested_log_attributes_are_filtered() {
+        let filter = AttributeFilter {
+            allowed: vec!["error".into(), "error.type".into(), "error.stack.*".into()],
+            max_length: 4,
+        };
+        let kvlist = |values| proto::AnyValue {
+            value: Some(ProtoValue::KvlistValue(proto::KeyValueList { values })),
+        };
+        let nested = proto::KeyValue {
+            key: "error".into(),
+            value: Some(kvlist(vec![
+                string("type", "TypeError"),
+                string("user_email", "a@example.com"),
+                proto::KeyValue {
+                    key: "stack".into(),
+                    value: Some(kvlist(vec![string("frame", "app.js")])),
+                },
+            ])),
+        };
+
+        let (attributes, dropped) = filter.log_attributes(vec![nested, string("token", "x")]);
+        assert_eq!(dropped, 1);
+        let [(key, AnyValue::Map(map))] = attributes.as_slice() else {
+            panic!("unexpected attributes: {attributes:?}");
+        };
+        assert_eq!(key.as_str(), "error");
+        // error.user_email 不在白名单；error.stack 本身未列出，其子键也随之丢弃
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&Key::new("type")), Some(&AnyValue::from("Type")));
+    }
+}
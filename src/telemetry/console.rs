@@ -0,0 +1,170 @@
+use std::fmt;
+
+use opentelemetry::trace::{SpanId, TraceContextExt, TraceId};
+use tracing::{Event, Subscriber};
+use tracing_opentelemetry::OtelData;
+use tracing_subscriber::Layer;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::{Format, Writer};
+use tracing_subscriber::fmt::time::{FormatTime, LocalTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+
+use crate::config::ConsoleFormat;
+
+/// 构建控制台输出层，每行日志都带上当前 span 的 trace_id / span_id
+pub fn layer<S>(format: ConsoleFormat) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    writer_layer(format, std::io::stdout, true)
+}
+
+/// 以指定格式输出到任意 writer，文件等非终端目标应关闭 `ansi`
+pub fn writer_layer<S, W>(
+    format: ConsoleFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let text = || {
+        Format::default()
+            .without_time()
+            .with_file(true)
+            .with_line_number(true)
+    };
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+
+    match format {
+        ConsoleFormat::Full => fmt
+            .event_format(TraceIds::text(text(), LocalTime::rfc_3339()))
+            .boxed(),
+        ConsoleFormat::Compact => fmt
+            .event_format(TraceIds::text(text().compact(), LocalTime::rfc_3339()))
+            .boxed(),
+        ConsoleFormat::Pretty => fmt
+            .event_format(TraceIds::text(text().pretty(), LocalTime::rfc_3339()))
+            .boxed(),
+        ConsoleFormat::Json => fmt
+            .json()
+            .with_ansi(false)
+            .event_format(TraceIds::json(
+                Format::default()
+                    .json()
+                    .with_timer(LocalTime::rfc_3339())
+                    .with_file(true)
+                    .with_line_number(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            ))
+            .boxed(),
+    }
+}
+
+/// 在内层格式的输出中加入 OTel trace_id / span_id
+///
+/// 文本格式：时间戳后紧跟 `trace_id=… span_id=…`；JSON 格式：作为顶层字段插入
+struct TraceIds<F, T> {
+    inner: F,
+    json: bool,
+    /// 文本格式自行输出时间戳，使 trace_id 紧跟其后
+    timer: T,
+}
+
+impl<F, T: FormatTime> TraceIds<F, T> {
+    fn text(inner: F, timer: T) -> Self {
+        Self {
+            inner,
+            json: false,
+            timer,
+        }
+    }
+}
+
+impl<F> TraceIds<F, ()> {
+    fn json(inner: F) -> Self {
+        Self {
+            inner,
+            json: true,
+            timer: (),
+        }
+    }
+}
+
+impl<S, N, F, T> FormatEvent<S, N> for TraceIds<F, T>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let ids = current_ids(ctx);
+
+        if self.json {
+            let Some((trace_id, span_id)) = ids else {
+                return self.inner.format_event(ctx, writer, event);
+            };
+            let mut line = String::new();
+            self.inner
+                .format_event(ctx, Writer::new(&mut line), event)?;
+            return match line.strip_prefix('{') {
+                Some(rest) => write!(
+                    writer,
+                    "{{\"trace_id\":\"{trace_id}\",\"span_id\":\"{span_id}\",{rest}"
+                ),
+                None => writer.write_str(&line),
+            };
+        }
+
+        let (dim, reset) = if writer.has_ansi_escapes() {
+            ("\x1b[2m", "\x1b[0m")
+        } else {
+            ("", "")
+        };
+        write!(writer, "{dim}")?;
+        self.timer.format_time(&mut writer)?;
+        if let Some((trace_id, span_id)) = ids {
+            write!(writer, " trace_id={trace_id} span_id={span_id}")?;
+        }
+        write!(writer, "{reset} ")?;
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
+/// 从当前 tracing span 的扩展中读取 OTel trace_id / span_id，不触发采样
+fn current_ids<S, N>(ctx: &FmtContext<'_, S, N>) -> Option<(TraceId, SpanId)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    span_ids(&ctx.lookup_current()?)
+}
+
+/// 从 span 扩展中读取 OTel trace_id / span_id；根 span 的 trace_id 尚未写入父 context，取 builder 上的值
+pub(super) fn span_ids<S>(span: &SpanRef<'_, S>) -> Option<(TraceId, SpanId)>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    let extensions = span.extensions();
+    let data = extensions.get::<OtelData>()?;
+
+    let parent = data.parent_cx.span();
+    let parent = parent.span_context();
+    let trace_id = if parent.is_valid() {
+        parent.trace_id()
+    } else {
+        data.builder.trace_id?
+    };
+    Some((trace_id, data.builder.span_id?))
+}
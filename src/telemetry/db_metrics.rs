@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Histogram;
+use sea_orm::sqlx::SqlitePool;
+use sea_orm::{DatabaseBackend, DatabaseConnection};
+
+use crate::config::DatabaseConfig;
+
+/// 查询耗时直方图桶（秒），与 OTel 数据库语义约定推荐值一致
+const DURATION_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0];
+
+/// 为数据库连接挂上查询耗时指标与慢查询日志，并导出连接池指标
+///
+/// 须在 `init_telemetry` 之后、连接放入 `AppState` 之前调用
+pub fn instrument(db: &mut DatabaseConnection, config: &DatabaseConfig) {
+    let meter = opentelemetry::global::meter("axum-otel-demo");
+    let duration = meter
+        .f64_histogram("db.client.operation.duration")
+        .with_description("Duration of database client operations.")
+        .with_unit("s")
+        .with_boundaries(DURATION_BUCKETS.to_vec())
+        .build();
+    let system = system_name(db.get_database_backend());
+    let slow_threshold = Duration::from_millis(config.slow_query_ms);
+
+    db.set_metric_callback(move |info| {
+        record(&duration, system, slow_threshold, info);
+    });
+
+    if db.get_database_backend() == DatabaseBackend::Sqlite {
+        register_pool(db.get_sqlite_connection_pool().clone());
+    }
+}
+
+fn system_name(backend: DatabaseBackend) -> &'static str {
+    match backend {
+        DatabaseBackend::MySql => "mysql",
+        DatabaseBackend::Postgres => "postgresql",
+        DatabaseBackend::Sqlite => "sqlite",
+        _ => "other_sql",
+    }
+}
+
+fn record(
+    duration: &Histogram<f64>,
+    system: &'static str,
+    slow_threshold: Duration,
+    info: &sea_orm::metric::Info<'_>,
+) {
+    let sql = info.statement.sql.as_str();
+    let (operation, table) = operation_and_table(sql);
+
+    let mut attributes = vec![
+        KeyValue::new("db.system.name", system),
+        KeyValue::new("db.operation.name", operation.clone()),
+    ];
+    if let Some(table) = &table {
+        attributes.push(KeyValue::new("db.collection.name", table.clone()));
+    }
+    if info.failed {
+        attributes.push(KeyValue::new("error.type", "DbErr"));
+    }
+    duration.record(info.elapsed.as_secs_f64(), &attributes);
+
+    if info.elapsed >= slow_threshold {
+        tracing::warn!(
+            db.operation.name = %operation,
+            db.collection.name = table.as_deref(),
+            duration_ms = info.elapsed.as_millis() as u64,
+            failed = info.failed,
+            db.query.text = %sanitize_sql(sql),
+            "Slow query"
+        );
+    }
+}
+
+/// 连接池大小与空闲连接数
+///
+/// sqlx 不暴露获取连接的等待时间，也没有 acquire 钩子，因此不导出 `db.client.connection.wait_time`；
+/// 连接池耗尽表现为 used 连接数贴近上限，获取超时以 `DbErr::ConnectionAcquire` 返回并记录在请求 span 上
+fn register_pool(pool: SqlitePool) {
+    let meter = opentelemetry::global::meter("axum-otel-demo");
+    let pool_name = KeyValue::new("db.client.connection.pool.name", "default");
+
+    let counted = pool.clone();
+    let name = pool_name.clone();
+    meter
+        .i64_observable_gauge("db.client.connection.count")
+        .with_description("Number of connections currently in the pool by state.")
+        .with_unit("{connection}")
+        .with_callback(move |observer| {
+            let size = counted.size() as i64;
+            let idle = counted.num_idle() as i64;
+            observer.observe(
+                idle,
+                &[
+                    name.clone(),
+                    KeyValue::new("db.client.connection.state", "idle"),
+                ],
+            );
+            observer.observe(
+                (size - idle).max(0),
+                &[
+                    name.clone(),
+                    KeyValue::new("db.client.connection.state", "used"),
+                ],
+            );
+        })
+        .build();
+
+    let max = pool.options().get_max_connections() as i64;
+    let attributes = [pool_name];
+    meter
+        .i64_observable_gauge("db.client.connection.max")
+        .with_description("The maximum number of open connections allowed.")
+        .with_unit("{connection}")
+        .with_callback(move |observer| observer.observe(max, &attributes))
+        .build();
+}
+
+/// 从 SQL 中解析操作名与表名，用作指标标签
+fn operation_and_table(sql: &str) -> (String, Option<String>) {
+    let mut words = sql.split_whitespace();
+    let operation = words.next().unwrap_or_default().to_ascii_uppercase();
+    let keyword = match operation.as_str() {
+        "SELECT" | "DELETE" => "FROM",
+        "INSERT" | "REPLACE" => "INTO",
+        "UPDATE" => return (operation, words.next().and_then(table_name)),
+        "CREATE" | "DROP" | "ALTER" => "TABLE",
+        _ => return (operation, None),
+    };
+
+    let table = words
+        .skip_while(|w| !w.eq_ignore_ascii_case(keyword))
+        .skip(1)
+        .find(|w| {
+            !["IF", "NOT", "EXISTS"]
+                .iter()
+                .any(|k| w.eq_ignore_ascii_case(k))
+        })
+        .and_then(table_name);
+    (operation, table)
+}
+
+/// 去掉引号与 schema 前缀；子查询等非表名返回 None
+fn table_name(word: &str) -> Option<String> {
+    let word = word.trim_end_matches([',', ';', '(']);
+    let word = word.rsplit('.').next().unwrap_or(word);
+    let name = word.trim_matches(['"', '`', '[', ']']);
+    (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .then(|| name.to_string())
+}
+
+/// 把 SQL 中的字符串与数字字面量替换为 `?`，参数化的占位符保持不变
+fn sanitize_sql(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    // 上一个字符是否属于标识符，避免把 `t1` 中的数字当成字面量
+    let mut in_word = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // 单引号字符串，`''` 为转义
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                out.push('?');
+                in_word = false;
+            }
+            c if c.is_ascii_digit() && !in_word => {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+                {
+                    chars.next();
+                }
+                out.push('?');
+            }
+            c => {
+                in_word = c.is_alphanumeric() || c == '_' || c == '"' || c == '$';
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_table_and_sanitize() {
+        assert_eq!(
+            operation_and_table(r#"SELECT "users"."id" FROM "users" WHERE "users"."id" = ?"#),
+            ("SELECT".to_string(), Some("users".to_string()))
+        );
+        assert_eq!(
+            operation_and_table(r#"insert INTO "inquiries" ("name") VALUES (?)"#),
+            ("INSERT".to_string(), Some("inquiries".to_string()))
+        );
+        assert_eq!(
+            operation_and_table(r#"CREATE TABLE IF NOT EXISTS "users" ( "id" integer )"#),
+            ("CREATE".to_string(), Some("users".to_string()))
+        );
+        assert_eq!(
+            operation_and_table("SELECT sqlite_version()"),
+            ("SELECT".to_string(), None)
+        );
+
+        assert_eq!(
+            sanitize_sql("SELECT * FROM t1 WHERE name = 'o''brien' AND age > 42 AND id = $1"),
+            "SELECT * FROM t1 WHERE name = ? AND age > ? AND id = $1"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::OnceLock;
+
+use hmac::{Hmac, Mac};
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::trace::Span as _;
+use opentelemetry::{Context, InstrumentationScope, KeyValue};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::logs::{LogProcessor, SdkLogRecord};
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use sha2::Sha256;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::config::{EnduserConfig, EnduserIdMode};
+use crate::middleware::CurrentUser;
+
+/// span 属性与 baggage 共用的键
+pub const ENDUSER_ID: &str = "enduser.id";
+/// 摘要保留的字节数（十六进制后 32 个字符）
+const DIGEST_BYTES: usize = 16;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+#[derive(Debug)]
+struct Settings {
+    enabled: bool,
+    baggage: bool,
+    /// `Some` 时记录 HMAC 摘要
+    hash_key: Option<Vec<u8>>,
+}
+
+impl Settings {
+    fn new(config: &EnduserConfig) -> Self {
+        // 加载配置时已校验 hash 模式必有密钥
+        let hash_key = (config.id == EnduserIdMode::Hash).then(|| {
+            config
+                .hash_key
+                .as_ref()
+                .expect("telemetry.enduser.hash_key is required in hash mode")
+                .as_bytes()
+                .to_vec()
+        });
+        Self {
+            enabled: config.enabled,
+            baggage: config.baggage,
+            hash_key,
+        }
+    }
+
+    fn id(&self, id: i32) -> String {
+        match &self.hash_key {
+            Some(key) => digest(key, &id.to_string()),
+            None => id.to_string(),
+        }
+    }
+}
+
+/// 按配置初始化身份记录方式
+pub fn init(config: &EnduserConfig) {
+    let _ = SETTINGS.set(Settings::new(config));
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings::new(&EnduserConfig::default()))
+}
+
+/// 是否需要把 baggage 中的 `enduser.id` 复制到下游 span 与日志
+pub fn baggage_enabled() -> bool {
+    let settings = settings();
+    settings.enabled && settings.baggage
+}
+
+/// 在当前请求 span 上记录已认证管理员
+///
+/// 返回在请求 span 上下文基础上加入 `enduser.id` baggage 的 OTel 上下文，
+/// 调用方在其中执行后续处理；未启用 baggage 时返回 None
+pub fn record(user: &CurrentUser) -> Option<Context> {
+    let settings = settings();
+    if !settings.enabled {
+        return None;
+    }
+    let id = settings.id(user.id);
+    let span = tracing::Span::current();
+    span.record("enduser.id", id.as_str());
+    span.record("enduser.role", user.role.as_str());
+
+    settings.baggage.then(|| {
+        let cx = span.context();
+        let baggage: Vec<KeyValue> = cx
+            .baggage()
+            .iter()
+            .filter(|(k, _)| k.as_str() != ENDUSER_ID)
+            .map(|(k, (v, _))| KeyValue::new(k.clone(), v.clone()))
+            .chain(std::iter::once(KeyValue::new(ENDUSER_ID, id)))
+            .collect();
+        cx.with_baggage(baggage)
+    })
+}
+
+/// 去掉入站 baggage 中客户端自带的 `enduser.*`，请求身份只能由认证中间件写入
+pub fn strip_inbound(cx: Context) -> Context {
+    let baggage = cx.baggage();
+    if !baggage.iter().any(|(k, _)| is_enduser_key(k.as_str())) {
+        return cx;
+    }
+    let kept: Vec<KeyValue> = baggage
+        .iter()
+        .filter(|(k, _)| !is_enduser_key(k.as_str()))
+        .map(|(k, (v, _))| KeyValue::new(k.clone(), v.clone()))
+        .collect();
+    cx.with_baggage(kept)
+}
+
+fn is_enduser_key(key: &str) -> bool {
+    key.starts_with("enduser.")
+}
+
+/// HMAC-SHA256 截断后的十六进制摘要
+fn digest(key: &[u8], id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(id.as_bytes());
+    mac.finalize().into_bytes()[..DIGEST_BYTES]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn baggage_id(cx: &Context) -> Option<String> {
+    cx.baggage().get(ENDUSER_ID).map(|v| v.to_string())
+}
+
+/// 把 baggage 中的 `enduser.id` 写入 span 属性，须排在其他处理器之前
+#[derive(Debug)]
+pub struct EnduserSpanProcessor;
+
+impl SpanProcessor for EnduserSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        // tracing-opentelemetry 在 span 关闭时才创建 OTel span，父上下文不含请求中途加入的 baggage，
+        // 此时当前上下文仍是认证中间件设置的请求上下文
+        if let Some(id) = baggage_id(cx).or_else(|| Context::map_current(baggage_id)) {
+            span.set_attribute(KeyValue::new(ENDUSER_ID, id));
+        }
+    }
+
+    fn on_end(&self, _span: SpanData) {}
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+/// 把 baggage 中的 `enduser.id` 写入日志属性，须排在其他处理器之前
+#[derive(Debug)]
+pub struct EnduserLogProcessor;
+
+impl LogProcessor for EnduserLogProcessor {
+    fn emit(&self, record: &mut SdkLogRecord, _instrumentation: &InstrumentationScope) {
+        if let Some(id) = Context::map_current(baggage_id) {
+            opentelemetry::logs::LogRecord::add_attribute(record, ENDUSER_ID, id);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry_sdk::propagation::BaggagePropagator;
+
+    #[test]
+    fn test_inbound_enduser_baggage_is_stripped() {
+        let headers = HashMap::from([(
+            "baggage".to_string(),
+            "enduser.id=1,enduser.role=admin,tenant=acme".to_string(),
+        )]);
+        let cx = strip_inbound(BaggagePropagator::new().extract(&headers));
+
+        assert_eq!(baggage_id(&cx), None);
+        assert_eq!(cx.baggage().get("enduser.role"), None);
+        assert_eq!(cx.baggage().get("tenant").map(|v| v.to_string()), Some("acme".into()));
+    }
+
+    #[test]
+    fn test_hashed_id_is_stable_and_keyed() {
+        let config = EnduserConfig {
+            id: EnduserIdMode::Hash,
+            hash_key: Some("secret".into()),
+            ..EnduserConfig::default()
+        };
+        let settings = Settings::new(&config);
+        let id = settings.id(42);
+        assert_eq!(id.len(), DIGEST_BYTES * 2);
+        assert_eq!(id, settings.id(42));
+        assert_ne!(id, settings.id(43));
+
+        let other = Settings::new(&EnduserConfig {
+            hash_key: Some("other".into()),
+            ..config
+        });
+        assert_ne!(id, other.id(42));
+        assert_eq!(Settings::new(&EnduserConfig::default()).id(42), "42");
+    }
+}
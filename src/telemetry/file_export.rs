@@ -0,0 +1,121 @@
+use std::fmt;
+use std::future::{self, Future};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
+use opentelemetry_proto::transform::trace::tonic::group_spans_by_resource_and_scope;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::logs::{LogBatch, LogExporter};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+use serde::Serialize;
+
+use super::rolling_file::RollingFile;
+use crate::config::FileExportConfig;
+
+/// 导出文件的扩展名，每行一个 JSON 对象
+pub const EXTENSION: &str = "jsonl";
+
+/// 创建写入 `{prefix}-traces.jsonl` 的 span 导出器
+pub fn span_exporter(config: &FileExportConfig) -> io::Result<FileSpanExporter> {
+    Ok(FileSpanExporter(JsonLines::open(config, "traces")?))
+}
+
+/// 创建写入 `{prefix}-logs.jsonl` 的日志导出器
+pub fn log_exporter(config: &FileExportConfig) -> io::Result<FileLogExporter> {
+    Ok(FileLogExporter(JsonLines::open(config, "logs")?))
+}
+
+/// 按行写入 OTLP-JSON 导出请求的滚动文件
+struct JsonLines {
+    writer: Arc<Mutex<RollingFile>>,
+    resource: ResourceAttributesWithSchema,
+}
+
+impl JsonLines {
+    fn open(config: &FileExportConfig, signal: &str) -> io::Result<Self> {
+        let file = RollingFile::open_with_extension(&config.file_config(signal), EXTENSION)?;
+        Ok(Self {
+            writer: Arc::new(Mutex::new(file)),
+            resource: ResourceAttributesWithSchema::default(),
+        })
+    }
+
+    /// 写入同一文件、资源属性独立的副本
+    fn share(&self) -> Self {
+        Self {
+            writer: self.writer.clone(),
+            resource: ResourceAttributesWithSchema::default(),
+        }
+    }
+
+    /// 整行一次写入，避免切分时一条记录跨两个文件
+    fn write(&self, request: &impl Serialize) -> OTelSdkResult {
+        let mut line = serde_json::to_vec(request)
+            .map_err(|e| OTelSdkError::InternalFailure(format!("序列化失败: {e}")))?;
+        line.push(b'\n');
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer
+            .write_all(&line)
+            .and_then(|_| writer.flush())
+            .map_err(|e| OTelSdkError::InternalFailure(format!("写入导出文件失败: {e}")))
+    }
+}
+
+impl fmt::Debug for JsonLines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines").finish_non_exhaustive()
+    }
+}
+
+/// 每批 span 写为一行 `ExportTraceServiceRequest`
+#[derive(Debug)]
+pub struct FileSpanExporter(JsonLines);
+
+impl FileSpanExporter {
+    /// 写入同一文件的导出器，可设置不同的 Resource（如浏览器端 spans）
+    pub fn share(&self) -> Self {
+        Self(self.0.share())
+    }
+}
+
+impl SpanExporter for FileSpanExporter {
+    fn export(&self, batch: Vec<SpanData>) -> impl Future<Output = OTelSdkResult> + Send {
+        let request = ExportTraceServiceRequest {
+            resource_spans: group_spans_by_resource_and_scope(batch, &self.0.resource),
+        };
+        future::ready(self.0.write(&request))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.0.resource = resource.into();
+    }
+}
+
+/// 每批日志写为一行 `ExportLogsServiceRequest`
+#[derive(Debug)]
+pub struct FileLogExporter(JsonLines);
+
+impl FileLogExporter {
+    /// 写入同一文件的导出器，可设置不同的 Resource（如浏览器端日志）
+    pub fn share(&self) -> Self {
+        Self(self.0.share())
+    }
+}
+
+impl LogExporter for FileLogExporter {
+    fn export(&self, batch: LogBatch<'_>) -> impl Future<Output = OTelSdkResult> + Send {
+        let request = ExportLogsServiceRequest {
+            resource_logs: group_logs_by_resource_and_scope(batch, &self.0.resource),
+        };
+        future::ready(self.0.write(&request))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.0.resource = resource.into();
+    }
+}
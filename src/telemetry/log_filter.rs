@@ -0,0 +1,166 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+use crate::utils::time;
+
+static LOG_FILTER: OnceLock<LogFilter> = OnceLock::new();
+
+/// 运行时可替换的全局日志过滤器
+struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    state: Mutex<FilterState>,
+}
+
+#[derive(Debug, Clone)]
+struct FilterState {
+    /// 启动时生效的过滤规则，临时调整到期后回退到这里
+    default: String,
+    current: String,
+    expires_at: Option<NaiveDateTime>,
+    /// 每次调整递增，避免旧的回退任务覆盖新的设置
+    generation: u64,
+}
+
+/// 当前过滤规则快照
+#[derive(Debug, Clone)]
+pub struct FilterSnapshot {
+    pub default: String,
+    pub current: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// 构建可重载的过滤层：`RUST_LOG` 优先，其次为配置文件中的规则
+///
+/// 规则无法解析时回退到 `info`，并把原因作为第二个返回值交给调用方记录
+pub fn init(directives: &str) -> (reload::Layer<EnvFilter, Registry>, Option<String>) {
+    let directives = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| directives.to_string());
+
+    let (filter, directives, error) = match EnvFilter::try_new(&directives) {
+        Ok(filter) => (filter, directives, None),
+        Err(e) => (
+            EnvFilter::new("info"),
+            "info".to_string(),
+            Some(format!(
+                "日志过滤规则 `{directives}` 无效，已回退到 info: {e}"
+            )),
+        ),
+    };
+
+    let (layer, handle) = reload::Layer::new(filter);
+    let _ = LOG_FILTER.set(LogFilter {
+        handle,
+        state: Mutex::new(FilterState {
+            default: directives.clone(),
+            current: directives,
+            expires_at: None,
+            generation: 0,
+        }),
+    });
+    (layer, error)
+}
+
+/// 获取当前过滤规则
+pub fn current() -> Option<FilterSnapshot> {
+    let filter = LOG_FILTER.get()?;
+    let state = filter.state.lock().unwrap_or_else(|e| e.into_inner());
+    Some(FilterSnapshot {
+        default: state.default.clone(),
+        current: state.current.clone(),
+        expires_at: state.expires_at,
+    })
+}
+
+/// 替换过滤规则；指定 `ttl` 时到期自动回退到启动时的规则
+pub fn update(directives: &str, ttl: Option<Duration>) -> Result<FilterSnapshot, String> {
+    let filter = LOG_FILTER.get().ok_or("日志过滤器未初始化")?;
+    let new_filter = EnvFilter::try_new(directives).map_err(|e| format!("无效的过滤规则: {e}"))?;
+
+    let generation = {
+        let mut state = filter.state.lock().unwrap_or_else(|e| e.into_inner());
+        filter
+            .handle
+            .reload(new_filter)
+            .map_err(|e| e.to_string())?;
+        state.current = directives.to_string();
+        state.expires_at = ttl.and_then(|ttl| {
+            chrono::Duration::from_std(ttl)
+                .ok()
+                .map(|ttl| time::now() + ttl)
+        });
+        state.generation += 1;
+        state.generation
+    };
+
+    if let Some(ttl) = ttl {
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            revert(generation);
+        });
+    }
+
+    current().ok_or_else(|| "日志过滤器未初始化".to_string())
+}
+
+/// 立即回退到启动时的规则
+pub fn reset() -> Result<FilterSnapshot, String> {
+    let filter = LOG_FILTER.get().ok_or("日志过滤器未初始化")?;
+    let default = filter
+        .state
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .default
+        .clone();
+    update(&default, None)
+}
+
+/// 到期回退：期间若规则又被调整过则放弃
+fn revert(generation: u64) {
+    let Some(filter) = LOG_FILTER.get() else {
+        return;
+    };
+    let mut state = filter.state.lock().unwrap_or_else(|e| e.into_inner());
+    if state.generation != generation {
+        return;
+    }
+    // 启动规则已校验过，不会解析失败
+    if let Ok(default_filter) = EnvFilter::try_new(&state.default)
+        && filter.handle.reload(default_filter).is_ok()
+    {
+        state.current = state.default.clone();
+        state.expires_at = None;
+        state.generation += 1;
+        drop(state);
+        tracing::info!("Log filter reverted to default after TTL");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_reverts_after_ttl() {
+        let (layer, error) = init("info");
+        assert!(error.is_none());
+        let _guard = tracing::subscriber::set_default(Registry::default().with(layer));
+
+        assert!(update("info,sea_orm=[", None).is_err());
+
+        let snapshot = update("info,sea_orm=debug", Some(Duration::from_millis(50))).unwrap();
+        assert_eq!(snapshot.current, "info,sea_orm=debug");
+        assert!(snapshot.expires_at.is_some());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let snapshot = current().unwrap();
+        assert_eq!(snapshot.current, snapshot.default);
+        assert!(snapshot.expires_at.is_none());
+    }
+}
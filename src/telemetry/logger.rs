@@ -1,16 +1,95 @@
-use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{logs::SdkLoggerProvider, Resource};
+use std::time::Duration;
 
-/// 初始化 OTel Logger Provider，导出 logs 到 OTLP gRPC
-pub fn init_logger(resource: Resource, endpoint: &str) -> SdkLoggerProvider {
-    let exporter = opentelemetry_otlp::LogExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint)
-        .build()
-        .expect("Failed to create OTLP log exporter");
+use opentelemetry_sdk::{
+    logs::{BatchConfigBuilder, BatchLogProcessor, LogExporter, SdkLoggerProvider},
+    Resource,
+};
 
-    SdkLoggerProvider::builder()
-        .with_batch_exporter(exporter)
-        .with_resource(resource)
-        .build()
+use super::enduser::{self, EnduserLogProcessor};
+use super::file_export::FileLogExporter;
+use super::resilience::{self, BoundedProcessor, ResilientExporter, RetryPolicy};
+use crate::config::TelemetryConfig;
+
+/// 初始化 OTel Logger Provider，导出 logs 到 OTLP 与本地文件，两者都未启用时返回 None
+pub fn init_logger(
+    resource: Resource,
+    config: &TelemetryConfig,
+    otlp_exporter: Option<opentelemetry_otlp::LogExporter>,
+    file_exporter: Option<FileLogExporter>,
+) -> Option<SdkLoggerProvider> {
+    build(
+        resource,
+        config,
+        otlp_exporter,
+        file_exporter,
+        ["otlp", "file"],
+        enduser::baggage_enabled(),
+    )
+}
+
+/// 浏览器端日志使用独立的 Logger Provider，资源属性与服务端日志区分开
+pub fn init_browser_logger(
+    resource: Resource,
+    config: &TelemetryConfig,
+    otlp_exporter: Option<opentelemetry_otlp::LogExporter>,
+    file_exporter: Option<FileLogExporter>,
+) -> Option<SdkLoggerProvider> {
+    build(
+        resource,
+        config,
+        otlp_exporter,
+        file_exporter,
+        ["browser-otlp", "browser-file"],
+        false,
+    )
+}
+
+fn build(
+    resource: Resource,
+    config: &TelemetryConfig,
+    otlp_exporter: Option<opentelemetry_otlp::LogExporter>,
+    file_exporter: Option<FileLogExporter>,
+    [otlp_name, file_name]: [&'static str; 2],
+    enduser: bool,
+) -> Option<SdkLoggerProvider> {
+    if otlp_exporter.is_none() && file_exporter.is_none() {
+        return None;
+    }
+
+    let mut builder = SdkLoggerProvider::builder().with_resource(resource);
+    if enduser {
+        builder = builder.with_log_processor(EnduserLogProcessor);
+    }
+    if let Some(exporter) = otlp_exporter {
+        builder = builder.with_log_processor(batch_processor(exporter, otlp_name, config));
+    }
+    if let Some(exporter) = file_exporter {
+        builder = builder.with_log_processor(batch_processor(exporter, file_name, config));
+    }
+    Some(builder.build())
+}
+
+/// 有界队列计数 → 批处理 → 重试导出
+fn batch_processor<E: LogExporter + 'static>(
+    exporter: E,
+    name: &'static str,
+    config: &TelemetryConfig,
+) -> BoundedProcessor<BatchLogProcessor> {
+    let batch = &config.logs.batch;
+    let stats = resilience::register("logs", name, batch);
+    let exporter = ResilientExporter::new(
+        exporter,
+        RetryPolicy::new(&config.export_retry, batch),
+        stats.clone(),
+    );
+    let processor = BatchLogProcessor::builder(exporter)
+        .with_batch_config(
+            BatchConfigBuilder::default()
+                .with_max_queue_size(batch.max_queue_size)
+                .with_max_export_batch_size(batch.max_export_batch_size)
+                .with_scheduled_delay(Duration::from_millis(batch.scheduled_delay_ms))
+                .build(),
+        )
+        .build();
+    BoundedProcessor::new(processor, stats)
 }
@@ -0,0 +1,313 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Histogram, Meter, MeterProvider, UpDownCounter};
+use opentelemetry_otlp::ExporterBuildError;
+use opentelemetry_sdk::{
+    metrics::{PeriodicReader, SdkMeterProvider},
+    Resource,
+};
+
+use super::otlp;
+use super::prometheus::PrometheusExporter;
+use crate::config::{MetricsExporter, TelemetryConfig};
+
+/// 请求耗时直方图桶（秒），与 OTel HTTP 语义约定推荐值一致
+const DURATION_BUCKETS: [f64; 14] = [
+    0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// 应用自身指标的 instrumentation scope
+const METER_NAME: &str = "axum-otel-demo";
+
+/// body 大小直方图桶（字节）
+const BODY_SIZE_BUCKETS: [f64; 10] = [
+    0.0, 128.0, 512.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0,
+];
+
+/// 初始化 OTel Meter Provider
+///
+/// otlp 模式周期性推送到 OTLP collector；prometheus 模式返回拉取式导出器，由调用方挂载 `/metrics`
+pub fn init_meter_provider(
+    resource: Resource,
+    config: &TelemetryConfig,
+) -> Result<(SdkMeterProvider, Option<PrometheusExporter>), ExporterBuildError> {
+    let builder = SdkMeterProvider::builder().with_resource(resource);
+
+    let (provider, prometheus) = match config.metrics.exporter {
+        MetricsExporter::Otlp => {
+            let exporter = otlp::metric_exporter(config)?;
+            let provider = builder
+                .with_reader(PeriodicReader::builder(exporter).build())
+                .build();
+            (provider, None)
+        }
+        MetricsExporter::Prometheus => {
+            let exporter = PrometheusExporter::new();
+            let provider = builder.with_reader(exporter.reader()).build();
+            (provider, Some(exporter))
+        }
+    };
+
+    opentelemetry::global::set_meter_provider(provider.clone());
+    install(&provider);
+    Ok((provider, prometheus))
+}
+
+/// 以指定 MeterProvider 重建 HTTP 与业务指标，之后的记录都落到该 provider
+pub fn install(provider: &SdkMeterProvider) {
+    let meter = provider.meter(METER_NAME);
+    *HTTP_SERVER_METRICS
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(HttpServerMetrics::new(&meter)));
+    *BUSINESS_METRICS.write().unwrap_or_else(|e| e.into_inner()) =
+        Some(Arc::new(BusinessMetrics::new(&meter)));
+}
+
+/// 读取已安装的指标；尚未安装时从全局 MeterProvider 创建（未初始化遥测时为 noop）
+fn installed<T>(slot: &RwLock<Option<Arc<T>>>, create: fn(&Meter) -> T) -> Arc<T> {
+    if let Some(metrics) = slot.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return metrics.clone();
+    }
+    slot.write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| Arc::new(create(&opentelemetry::global::meter(METER_NAME))))
+        .clone()
+}
+
+/// HTTP 服务端指标（OTel HTTP 语义约定）
+pub struct HttpServerMetrics {
+    /// http.server.request.duration
+    pub request_duration: Histogram<f64>,
+    /// http.server.active_requests
+    pub active_requests: UpDownCounter<i64>,
+    /// http.server.request.body.size
+    pub request_body_size: Histogram<u64>,
+    /// http.server.response.body.size
+    pub response_body_size: Histogram<u64>,
+}
+
+static HTTP_SERVER_METRICS: RwLock<Option<Arc<HttpServerMetrics>>> = RwLock::new(None);
+
+/// 获取 HTTP 服务端指标
+///
+/// 须在 `init_telemetry` 之后调用，否则拿到的是 noop 指标
+pub fn http_server() -> Arc<HttpServerMetrics> {
+    installed(&HTTP_SERVER_METRICS, HttpServerMetrics::new)
+}
+
+impl HttpServerMetrics {
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            request_duration: meter
+                .f64_histogram("http.server.request.duration")
+                .with_description("Duration of HTTP server requests.")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            active_requests: meter
+                .i64_up_down_counter("http.server.active_requests")
+                .with_description("Number of active HTTP server requests.")
+                .with_unit("{request}")
+                .build(),
+            request_body_size: meter
+                .u64_histogram("http.server.request.body.size")
+                .with_description("Size of HTTP server request bodies.")
+                .with_unit("By")
+                .with_boundaries(BODY_SIZE_BUCKETS.to_vec())
+                .build(),
+            response_body_size: meter
+                .u64_histogram("http.server.response.body.size")
+                .with_description("Size of HTTP server response bodies.")
+                .with_unit("By")
+                .with_boundaries(BODY_SIZE_BUCKETS.to_vec())
+                .build(),
+        }
+    }
+}
+
+/// 业务指标：登录、人机验证、询盘与内容变更
+pub struct BusinessMetrics {
+    /// app.auth.login.attempts，按 `outcome` 区分结果
+    pub login_attempts: Counter<u64>,
+    /// app.auth.login.duration，包含 bcrypt 校验耗时
+    pub login_duration: Histogram<f64>,
+    /// app.captcha.challenges.issued
+    pub captcha_challenges: Counter<u64>,
+    /// app.captcha.challenge.duration，签发挑战的耗时
+    pub captcha_challenge_duration: Histogram<f64>,
+    /// app.captcha.verifications，按 `result` 区分结果
+    pub captcha_verifications: Counter<u64>,
+    /// app.inquiries.created，按 `product.id` 区分，不存在的产品记为 `unknown`
+    pub inquiries_created: Counter<u64>,
+    /// app.content.operations，按 `entity` 与 `operation` 区分
+    pub content_operations: Counter<u64>,
+}
+
+static BUSINESS_METRICS: RwLock<Option<Arc<BusinessMetrics>>> = RwLock::new(None);
+
+/// 获取业务指标
+pub fn business() -> Arc<BusinessMetrics> {
+    installed(&BUSINESS_METRICS, BusinessMetrics::new)
+}
+
+impl BusinessMetrics {
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            login_attempts: meter
+                .u64_counter("app.auth.login.attempts")
+                .with_description("Number of admin login attempts by outcome.")
+                .with_unit("{attempt}")
+                .build(),
+            login_duration: meter
+                .f64_histogram("app.auth.login.duration")
+                .with_description("Duration of admin login attempts by outcome.")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            captcha_challenges: meter
+                .u64_counter("app.captcha.challenges.issued")
+                .with_description("Number of ALTCHA challenges issued.")
+                .with_unit("{challenge}")
+                .build(),
+            captcha_challenge_duration: meter
+                .f64_histogram("app.captcha.challenge.duration")
+                .with_description("Time spent creating an ALTCHA challenge.")
+                .with_unit("s")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            captcha_verifications: meter
+                .u64_counter("app.captcha.verifications")
+                .with_description("Number of ALTCHA payload verifications by result.")
+                .with_unit("{verification}")
+                .build(),
+            inquiries_created: meter
+                .u64_counter("app.inquiries.created")
+                .with_description("Number of inquiries submitted by product.")
+                .with_unit("{inquiry}")
+                .build(),
+            content_operations: meter
+                .u64_counter("app.content.operations")
+                .with_description("Number of content changes by entity and operation.")
+                .with_unit("{operation}")
+                .build(),
+        }
+    }
+
+    /// 记录一次登录结果：success / captcha_failed / unknown_user / disabled / wrong_password / error
+    pub fn record_login(&self, outcome: &'static str, elapsed: Duration) {
+        let attributes = [KeyValue::new("outcome", outcome)];
+        self.login_attempts.add(1, &attributes);
+        self.login_duration.record(elapsed.as_secs_f64(), &attributes);
+    }
+
+    /// 记录一次内容变更，`operation` 为 create / update / delete
+    pub fn record_content(&self, entity: &'static str, operation: &'static str) {
+        self.content_operations.add(
+            1,
+            &[
+                KeyValue::new("entity", entity),
+                KeyValue::new("operation", operation),
+            ],
+        );
+    }
+}
+
+/// 测试辅助：安装以 ManualReader 读取的 provider，读出计数器的数据点
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::sync::Arc;
+
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::metrics::data::{ResourceMetrics, Sum};
+    use opentelemetry_sdk::metrics::reader::MetricReader;
+    use opentelemetry_sdk::metrics::{ManualReader, SdkMeterProvider};
+    use tokio::sync::{Mutex, MutexGuard};
+
+    use super::super::prometheus::SharedReader;
+
+    /// `install` 替换的是进程级指标，并行的测试须依次持有
+    static INSTALL_LOCK: Mutex<()> = Mutex::const_new(());
+
+    pub(crate) struct Installed {
+        _guard: MutexGuard<'static, ()>,
+        _provider: SdkMeterProvider,
+        reader: Arc<ManualReader>,
+    }
+
+    /// 安装新的 provider，返回值存活期间 `http_server()` / `business()` 都记录到它
+    pub(crate) async fn install() -> Installed {
+        let guard = INSTALL_LOCK.lock().await;
+        let reader = Arc::new(ManualReader::builder().build());
+        let provider = SdkMeterProvider::builder()
+            .with_reader(SharedReader(reader.clone()))
+            .build();
+        super::install(&provider);
+        Installed {
+            _guard: guard,
+            _provider: provider,
+            reader,
+        }
+    }
+
+    impl Installed {
+        pub(in crate::telemetry) fn reader(&self) -> SharedReader {
+            SharedReader(self.reader.clone())
+        }
+
+        /// 读取 u64 计数器的全部数据点
+        pub(crate) fn counter(&self, name: &str) -> Vec<(Vec<KeyValue>, u64)> {
+            let mut rm = ResourceMetrics {
+                resource: Resource::builder_empty().build(),
+                scope_metrics: Vec::new(),
+            };
+            self.reader.collect(&mut rm).unwrap();
+            rm.scope_metrics
+                .iter()
+                .flat_map(|scope| &scope.metrics)
+                .filter(|metric| metric.name == name)
+                .filter_map(|metric| metric.data.as_any().downcast_ref::<Sum<u64>>())
+                .flat_map(|sum| &sum.data_points)
+                .map(|dp| (dp.attributes.clone(), dp.value))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_business_counters() {
+        let installed = testing::install().await;
+
+        let business = business();
+        business.record_login("success", Duration::from_millis(5));
+        business.record_login("wrong_password", Duration::from_millis(5));
+        business.record_login("wrong_password", Duration::from_millis(5));
+        business.record_content("news", "create");
+
+        let mut logins = installed.counter("app.auth.login.attempts");
+        logins.sort_by_key(|(_, value)| *value);
+        assert_eq!(
+            logins,
+            vec![
+                (vec![KeyValue::new("outcome", "success")], 1),
+                (vec![KeyValue::new("outcome", "wrong_password")], 2),
+            ]
+        );
+        assert_eq!(
+            installed.counter("app.content.operations"),
+            vec![(
+                vec![
+                    KeyValue::new("entity", "news"),
+                    KeyValue::new("operation", "create"),
+                ],
+                1
+            )]
+        );
+    }
+}
@@ -1,75 +1,229 @@
+pub mod body_capture;
+pub mod browser;
+mod console;
+pub mod db_metrics;
+pub mod enduser;
+mod file_export;
+pub mod log_filter;
 mod logger;
+pub mod metrics;
+mod otlp;
+pub mod pprof;
 mod profiling;
+pub mod prometheus;
+mod propagation;
+pub mod redaction;
+pub mod resilience;
+mod resource;
+mod rolling_file;
+pub mod runtime_metrics;
+pub mod sampling;
 mod tracer;
+pub mod trace_tree;
+pub mod zpages;
 
-use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::{
+    logs::SdkLoggerProvider, metrics::SdkMeterProvider, trace::SdkTracerProvider,
+};
 use pyroscope::{PyroscopeAgent, pyroscope::PyroscopeAgentRunning};
-use tracing_subscriber::{filter::Targets, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::TelemetryConfig;
+use crate::config::{MetricsExporter, TelemetryConfig};
+use prometheus::PrometheusExporter;
 
 /// 可观测性资源句柄，持有需要在关闭时清理的 provider
 pub struct TelemetryGuard {
+    /// 导出 traces 时持有，关闭时写完批处理队列中的 span
+    pub tracer_provider: Option<SdkTracerProvider>,
     pub logger_provider: Option<SdkLoggerProvider>,
+    pub meter_provider: Option<SdkMeterProvider>,
+    /// prometheus 模式下的拉取式导出器，由 main 挂载 `/metrics`
+    pub prometheus: Option<PrometheusExporter>,
     pub pyroscope_agent: Option<PyroscopeAgent<PyroscopeAgentRunning>>,
+    /// 文件日志的后台写入线程，释放时写完剩余日志
+    pub file_guard: Option<WorkerGuard>,
 }
 
-/// 初始化全部可观测性组件：tracing + logging + profiling
-pub fn init_telemetry(config: &TelemetryConfig) -> TelemetryGuard {
-    let filter = Targets::new()
-        .with_default(tracing::Level::INFO)
-        .with_target("axum_otel_demo", tracing::Level::DEBUG)
-        .with_target("tower_http", tracing::Level::DEBUG)
-        .with_target("sea_orm", tracing::Level::DEBUG)
-        .with_target("pyroscope", tracing::level_filters::LevelFilter::OFF)
-        .with_target("pyroscope_pprofrs", tracing::level_filters::LevelFilter::OFF)
-        .with_target("Pyroscope", tracing::level_filters::LevelFilter::OFF)
-        .with_target("log", tracing::level_filters::LevelFilter::OFF);
-
-    let fmt_layer = tracing_subscriber::fmt::layer()
-        .with_timer(tracing_subscriber::fmt::time::LocalTime::rfc_3339())
-        .with_file(true)
-        .with_line_number(true);
-
-    let (telemetry_layer, logging_layer, logger_provider, pyroscope_agent) =
-        if config.otel_enabled {
-            let resource = tracer::create_resource();
-
-            let otel_tracer = tracer::init_tracer(resource.clone(), &config.otel_endpoint);
-            let logger_provider = logger::init_logger(resource.clone(), &config.otel_endpoint);
-            let pyroscope_agent = profiling::init_pyroscope(&config.pyroscope_endpoint)
-                .start()
-                .expect("Failed to start Pyroscope agent");
-
-            let telemetry_layer = tracing_opentelemetry::layer()
-                .with_tracer(otel_tracer)
-                .with_location(true);
-
-            let logging_layer =
-                opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(
-                    &logger_provider,
-                );
+/// 初始化全部可观测性组件：tracing + logging + metrics + profiling
+///
+/// `environment` 为启动参数 `--env`，记录为资源属性 `deployment.environment.name`
+pub fn init_telemetry(config: &TelemetryConfig, environment: &str) -> TelemetryGuard {
+    propagation::init_propagator(&config.propagators);
+    sampling::reload(config.sampling.clone());
+    redaction::init(&config.redaction);
+    body_capture::init(&config.body_capture);
+    zpages::init(&config.zpages);
+    pprof::init(&config.pprof);
+    enduser::init(&config.enduser);
 
+    let (filter, filter_error) = log_filter::init(&config.log_filter);
+
+    let fmt_layer = console::layer(config.console_format);
+
+    // 各信号独立初始化，失败时记录原因并降级，subscriber 就绪后统一输出
+    let mut failures: Vec<String> = filter_error.into_iter().collect();
+
+    let (file_layer, file_guard) = config
+        .file
+        .enabled
+        .then(|| rolling_file::init(&config.file))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("file 日志初始化失败，已停用: {e}")))
+                .ok()
+        })
+        .map(|(writer, guard)| {
             (
-                Some(telemetry_layer),
-                Some(logging_layer),
-                Some(logger_provider),
-                Some(pyroscope_agent),
+                console::writer_layer(config.file.format, writer, false),
+                guard,
             )
+        })
+        .unzip();
+    let resource = resource::detect(&config.service, environment);
+
+    // OTLP 与本地文件导出相互独立，任一失败只停用对应的导出
+    let otlp_span_exporter = (config.otel_enabled && config.traces.enabled)
+        .then(|| otlp::span_exporter(config))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("traces 初始化失败，已停用: {e}")))
+                .ok()
+        });
+    let file_span_exporter = (config.file_export.enabled && config.file_export.traces)
+        .then(|| file_export::span_exporter(&config.file_export))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("traces 文件导出初始化失败，已停用: {e}")))
+                .ok()
+        });
+    let file_export_traces = file_span_exporter.is_some();
+    let file_log_exporter = (config.file_export.enabled && config.file_export.logs)
+        .then(|| file_export::log_exporter(&config.file_export))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("logs 文件导出初始化失败，已停用: {e}")))
+                .ok()
+        });
+    // 浏览器端数据复用同一组导出配置，须在导出器移交给 provider 前创建
+    failures.extend(browser::init(
+        config,
+        environment,
+        file_span_exporter.as_ref(),
+        file_log_exporter.as_ref(),
+    ));
+    let traces_enabled = otlp_span_exporter.is_some() || file_export_traces;
+    // 未导出 traces 时仍生成 trace_id / span_id，供控制台日志关联
+    let tracer_provider = tracer::init_tracer(
+        resource.clone(),
+        config,
+        otlp_span_exporter,
+        file_span_exporter,
+    );
+    let telemetry_layer = tracing_opentelemetry::layer()
+        .with_tracer(tracer_provider.tracer("axum-otel-demo"))
+        .with_location(true);
+    let tracer_provider = traces_enabled.then(|| {
+        opentelemetry::global::set_tracer_provider(tracer_provider.clone());
+        tracer_provider
+    });
+
+    let otlp_log_exporter = (config.otel_enabled && config.logs.enabled)
+        .then(|| otlp::log_exporter(config))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("logs 初始化失败，已停用: {e}")))
+                .ok()
+        });
+    let file_export_logs = file_log_exporter.is_some();
+    let logger_provider = logger::init_logger(
+        resource.clone(),
+        config,
+        otlp_log_exporter,
+        file_log_exporter,
+    );
+    let logging_layer = logger_provider.as_ref().map(|provider| {
+        opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(provider)
+    });
+
+    let pyroscope_agent = (config.otel_enabled && config.profiling.enabled)
+        .then(|| profiling::init_pyroscope(&config.profiling))
+        .and_then(|result| {
+            result
+                .map_err(|e| failures.push(format!("profiling 初始化失败，已停用: {e}")))
+                .ok()
+        });
+    // pprof 采样器全进程唯一：Pyroscope 启动成功后按需 CPU profile 停用，启动时即确定
+    let cpu_reserved = pyroscope_agent.is_some() && config.pprof.enabled;
+    if cpu_reserved {
+        pprof::reserve_cpu_for_pyroscope();
+    }
+    let profiling_layer = pyroscope_agent
+        .as_ref()
+        .filter(|_| config.profiling.span_labels)
+        .map(profiling::SpanLabelsLayer::new);
+
+    // Prometheus 模式不依赖 collector，即使关闭 OTel 也照常采集
+    let (meter_provider, prometheus) =
+        if config.otel_enabled || config.metrics.exporter == MetricsExporter::Prometheus {
+            match metrics::init_meter_provider(resource, config) {
+                Ok((provider, prometheus)) => (Some(provider), prometheus),
+                Err(e) => {
+                    failures.push(format!("metrics 初始化失败，已停用: {e}"));
+                    (None, None)
+                }
+            }
         } else {
-            (None, None, None, None)
+            (None, None)
         };
+    if meter_provider.is_some() && config.metrics.runtime {
+        runtime_metrics::start(&config.metrics);
+    }
+    if meter_provider.is_some() {
+        resilience::register_metrics();
+    }
 
     tracing_subscriber::registry()
         .with(filter)
         .with(telemetry_layer)
+        .with(profiling_layer)
         .with(logging_layer)
         .with(fmt_layer)
+        .with(file_layer)
+        .with(zpages::log_layer())
         .init();
 
+    for failure in &failures {
+        tracing::error!("{failure}");
+    }
+    if cpu_reserved {
+        tracing::warn!(
+            "Pyroscope owns the CPU sampler, /debug/pprof/profile is disabled; set telemetry.profiling.enabled = false to use it"
+        );
+    }
+    if config.pyroscope_endpoint.is_some() {
+        tracing::warn!(
+            "`telemetry.pyroscope_endpoint` is deprecated, use `telemetry.profiling.endpoint` instead"
+        );
+    }
+    tracing::info!(
+        traces = traces_enabled,
+        logs = logger_provider.is_some(),
+        metrics = meter_provider.is_some(),
+        profiling = pyroscope_agent.is_some(),
+        file = file_guard.is_some(),
+        file_export = file_export_traces || file_export_logs,
+        "Telemetry initialized"
+    );
+
     TelemetryGuard {
+        tracer_provider,
         logger_provider,
+        meter_provider,
+        prometheus,
         pyroscope_agent,
+        file_guard,
     }
 }
 
@@ -77,14 +231,78 @@ impl TelemetryGuard {
     /// 优雅关闭所有可观测性组件
     pub fn shutdown(self) {
         if let Some(agent) = self.pyroscope_agent {
-            let agent_ready = agent.stop().expect("Failed to stop Pyroscope agent");
-            agent_ready.shutdown();
+            match agent.stop() {
+                Ok(agent_ready) => agent_ready.shutdown(),
+                Err(e) => eprintln!("Failed to stop Pyroscope agent: {:?}", e),
+            }
         }
 
-        if let Some(provider) = self.logger_provider {
-            if let Err(e) = provider.shutdown() {
-                eprintln!("Failed to shutdown logger provider: {:?}", e);
-            }
+        if let Some(provider) = self.meter_provider
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to shutdown meter provider: {:?}", e);
+        }
+
+        browser::shutdown();
+
+        if let Some(provider) = self.tracer_provider
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to shutdown tracer provider: {:?}", e);
         }
+
+        if let Some(provider) = self.logger_provider
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to shutdown logger provider: {:?}", e);
+        }
+
+        // 最后释放，确保上面的关闭日志也写入文件
+        drop(self.file_guard);
     }
 }
+
+/// 当前 span 所属的 trace_id；不在请求 span 内或上下文无效时返回 None
+pub fn current_trace_id() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+/// 在 span 对应的 OTel span 上追加事件
+///
+/// tracing 事件经 tracing-opentelemetry 才会成为 span 事件，同时也会输出一条日志；
+/// 这里直接写入待导出的 span，只记录在 trace 上
+pub fn add_span_event(
+    span: &tracing::Span,
+    name: &'static str,
+    attributes: Vec<opentelemetry::KeyValue>,
+) {
+    use tracing_subscriber::registry::LookupSpan;
+
+    span.with_subscriber(|(id, dispatch)| {
+        let Some(registry) = dispatch.downcast_ref::<tracing_subscriber::Registry>() else {
+            return;
+        };
+        let Some(span) = registry.span(id) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<tracing_opentelemetry::OtelData>() {
+            data.builder
+                .events
+                .get_or_insert_with(Vec::new)
+                .push(opentelemetry::trace::Event::new(
+                    name,
+                    std::time::SystemTime::now(),
+                    attributes,
+                    0,
+                ));
+        }
+    });
+}
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::{HeaderMap, HeaderValue, header::CONTENT_ENCODING};
+use flate2::write::GzEncoder;
+use opentelemetry_http::{Bytes, HttpClient, HttpError, Request, Response};
+use opentelemetry_otlp::{
+    Compression, ExporterBuildError, LogExporter, MetricExporter, Protocol, SpanExporter,
+    WithExportConfig, WithHttpConfig, WithTonicConfig,
+};
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+
+use crate::config::{
+    OtlpCompression, OtlpExporterConfig, OtlpProtocol, OtlpTlsConfig, TelemetryConfig,
+};
+
+/// 默认导出超时，与 OTel SDK 一致
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Signal {
+    Traces,
+    Logs,
+    Metrics,
+}
+
+impl Signal {
+    fn env_name(self) -> &'static str {
+        match self {
+            Signal::Traces => "TRACES",
+            Signal::Logs => "LOGS",
+            Signal::Metrics => "METRICS",
+        }
+    }
+
+    fn http_path(self) -> &'static str {
+        match self {
+            Signal::Traces => "/v1/traces",
+            Signal::Logs => "/v1/logs",
+            Signal::Metrics => "/v1/metrics",
+        }
+    }
+
+    fn section(self, config: &TelemetryConfig) -> &OtlpExporterConfig {
+        match self {
+            Signal::Traces => &config.otlp.traces,
+            Signal::Logs => &config.otlp.logs,
+            Signal::Metrics => &config.otlp.metrics,
+        }
+    }
+}
+
+/// 合并环境变量、信号级与顶层配置后，单个信号的导出设置
+#[derive(Debug, Clone)]
+pub struct ExporterSettings {
+    pub protocol: OtlpProtocol,
+    pub endpoint: String,
+    pub headers: HashMap<String, String>,
+    pub timeout: Duration,
+    pub compression: Option<OtlpCompression>,
+    pub tls: OtlpTlsConfig,
+}
+
+impl ExporterSettings {
+    /// 优先级：`OTEL_EXPORTER_OTLP_{SIGNAL}_*` > `OTEL_EXPORTER_OTLP_*` > 信号级配置 > 顶层配置
+    pub fn resolve(
+        config: &TelemetryConfig,
+        signal: Signal,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let section = signal.section(config);
+        let default = &config.otlp.default;
+        let lookup = |key: &str| {
+            env(&format!("OTEL_EXPORTER_OTLP_{}_{key}", signal.env_name()))
+                .or_else(|| env(&format!("OTEL_EXPORTER_OTLP_{key}")))
+        };
+
+        let protocol = lookup("PROTOCOL")
+            .and_then(|p| match p.trim() {
+                "grpc" => Some(OtlpProtocol::Grpc),
+                "http/protobuf" => Some(OtlpProtocol::HttpProtobuf),
+                "http/json" => Some(OtlpProtocol::HttpJson),
+                _ => None,
+            })
+            .or(section.protocol)
+            .or(default.protocol)
+            .unwrap_or(OtlpProtocol::Grpc);
+
+        // 信号级地址原样使用；基础地址在 HTTP 协议下追加 /v1/{signal}
+        let signal_endpoint = env(&format!(
+            "OTEL_EXPORTER_OTLP_{}_ENDPOINT",
+            signal.env_name()
+        ))
+        .or_else(|| section.endpoint.clone());
+        let endpoint = signal_endpoint.unwrap_or_else(|| {
+            let base = env("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .or_else(|| default.endpoint.clone())
+                .unwrap_or_else(|| config.otel_endpoint.clone());
+            match protocol {
+                OtlpProtocol::Grpc => base,
+                _ => format!("{}{}", base.trim_end_matches('/'), signal.http_path()),
+            }
+        });
+
+        let mut headers = default.headers.clone();
+        headers.extend(section.headers.clone());
+
+        let timeout = lookup("TIMEOUT")
+            .and_then(|t| t.trim().parse().ok())
+            .or(section.timeout_ms)
+            .or(default.timeout_ms)
+            .map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+
+        let compression = lookup("COMPRESSION")
+            .and_then(|c| match c.trim() {
+                "gzip" => Some(OtlpCompression::Gzip),
+                "zstd" => Some(OtlpCompression::Zstd),
+                "none" => Some(OtlpCompression::None),
+                _ => None,
+            })
+            .or(section.compression)
+            .or(default.compression);
+
+        let tls_config = section
+            .tls
+            .clone()
+            .or_else(|| default.tls.clone())
+            .unwrap_or_default();
+        let tls = OtlpTlsConfig {
+            ca_file: lookup("CERTIFICATE").or(tls_config.ca_file),
+            cert_file: lookup("CLIENT_CERTIFICATE").or(tls_config.cert_file),
+            key_file: lookup("CLIENT_KEY").or(tls_config.key_file),
+            domain_name: tls_config.domain_name,
+        };
+
+        Self {
+            protocol,
+            endpoint,
+            headers,
+            timeout,
+            compression,
+            tls,
+        }
+    }
+
+    fn from_env(config: &TelemetryConfig, signal: Signal) -> Self {
+        Self::resolve(config, signal, |key| std::env::var(key).ok())
+    }
+}
+
+/// 按配置构建 OTLP span 导出器
+pub fn span_exporter(config: &TelemetryConfig) -> Result<SpanExporter, ExporterBuildError> {
+    let settings = ExporterSettings::from_env(config, Signal::Traces);
+    match settings.protocol {
+        OtlpProtocol::Grpc => with_tonic(SpanExporter::builder().with_tonic(), &settings)?.build(),
+        _ => with_http(SpanExporter::builder().with_http(), &settings)?.build(),
+    }
+}
+
+/// 按配置构建 OTLP log 导出器
+pub fn log_exporter(config: &TelemetryConfig) -> Result<LogExporter, ExporterBuildError> {
+    let settings = ExporterSettings::from_env(config, Signal::Logs);
+    match settings.protocol {
+        OtlpProtocol::Grpc => with_tonic(LogExporter::builder().with_tonic(), &settings)?.build(),
+        _ => with_http(LogExporter::builder().with_http(), &settings)?.build(),
+    }
+}
+
+/// 按配置构建 OTLP metric 导出器
+pub fn metric_exporter(config: &TelemetryConfig) -> Result<MetricExporter, ExporterBuildError> {
+    let settings = ExporterSettings::from_env(config, Signal::Metrics);
+    match settings.protocol {
+        OtlpProtocol::Grpc => {
+            with_tonic(MetricExporter::builder().with_tonic(), &settings)?.build()
+        }
+        _ => with_http(MetricExporter::builder().with_http(), &settings)?.build(),
+    }
+}
+
+fn with_tonic<B: WithExportConfig + WithTonicConfig>(
+    builder: B,
+    settings: &ExporterSettings,
+) -> Result<B, ExporterBuildError> {
+    let headers = header_map(&settings.headers)?;
+    let mut builder = builder
+        .with_endpoint(&settings.endpoint)
+        .with_timeout(settings.timeout)
+        .with_metadata(MetadataMap::from_headers(headers));
+
+    match settings.compression {
+        Some(OtlpCompression::Gzip) => builder = builder.with_compression(Compression::Gzip),
+        Some(OtlpCompression::Zstd) => builder = builder.with_compression(Compression::Zstd),
+        Some(OtlpCompression::None) | None => {}
+    }
+
+    if settings.endpoint.starts_with("https://") || settings.tls.ca_file.is_some() {
+        let tls = &settings.tls;
+        let mut tls_config = ClientTlsConfig::new();
+        tls_config = match &tls.ca_file {
+            Some(path) => tls_config.ca_certificate(Certificate::from_pem(read_pem(path)?)),
+            None => tls_config.with_native_roots(),
+        };
+        if let (Some(cert), Some(key)) = (&tls.cert_file, &tls.key_file) {
+            tls_config = tls_config.identity(Identity::from_pem(read_pem(cert)?, read_pem(key)?));
+        }
+        if let Some(domain) = &tls.domain_name {
+            tls_config = tls_config.domain_name(domain);
+        }
+        builder = builder.with_tls_config(tls_config);
+    }
+
+    Ok(builder)
+}
+
+fn with_http<B: WithExportConfig + WithHttpConfig>(
+    builder: B,
+    settings: &ExporterSettings,
+) -> Result<B, ExporterBuildError> {
+    let protocol = match settings.protocol {
+        OtlpProtocol::HttpJson => Protocol::HttpJson,
+        _ => Protocol::HttpBinary,
+    };
+    let client = http_client(settings)?;
+    let builder = builder
+        .with_endpoint(&settings.endpoint)
+        .with_timeout(settings.timeout)
+        .with_protocol(protocol)
+        .with_headers(settings.headers.clone());
+
+    Ok(match settings.compression {
+        Some(OtlpCompression::Gzip) => builder.with_http_client(GzipHttpClient(client)),
+        Some(OtlpCompression::Zstd) => {
+            return Err(ExporterBuildError::UnsupportedCompressionAlgorithm(
+                "zstd over http".to_string(),
+            ));
+        }
+        Some(OtlpCompression::None) | None => builder.with_http_client(client),
+    })
+}
+
+/// 构建带自定义 CA / 客户端证书的阻塞 HTTP 客户端
+///
+/// reqwest 阻塞客户端内部持有独立运行时，不能在 tokio 运行时中创建，放到单独线程里构建
+fn http_client(
+    settings: &ExporterSettings,
+) -> Result<reqwest::blocking::Client, ExporterBuildError> {
+    let mut builder = reqwest::blocking::Client::builder().timeout(settings.timeout);
+    let tls = &settings.tls;
+    if let Some(path) = &tls.ca_file {
+        let ca = reqwest::Certificate::from_pem(&read_pem(path)?).map_err(internal)?;
+        builder = builder.add_root_certificate(ca);
+    }
+    if let (Some(cert), Some(key)) = (&tls.cert_file, &tls.key_file) {
+        let mut pem = read_pem(cert)?;
+        pem.extend(read_pem(key)?);
+        builder = builder.identity(reqwest::Identity::from_pem(&pem).map_err(internal)?);
+    }
+
+    std::thread::spawn(move || builder.build())
+        .join()
+        .map_err(|_| ExporterBuildError::ThreadSpawnFailed)?
+        .map_err(internal)
+}
+
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, ExporterBuildError> {
+    headers
+        .iter()
+        .map(|(k, v)| Ok((k.parse().map_err(internal)?, v.parse().map_err(internal)?)))
+        .collect()
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, ExporterBuildError> {
+    std::fs::read(path)
+        .map_err(|e| ExporterBuildError::InternalFailure(format!("读取证书 {path} 失败: {e}")))
+}
+
+fn internal(e: impl std::fmt::Display) -> ExporterBuildError {
+    ExporterBuildError::InternalFailure(e.to_string())
+}
+
+/// OTLP/HTTP 导出器不支持压缩，在发送前自行 gzip 请求体
+#[derive(Debug)]
+struct GzipHttpClient(reqwest::blocking::Client);
+
+#[async_trait]
+impl HttpClient for GzipHttpClient {
+    async fn send_bytes(&self, request: Request<Bytes>) -> Result<Response<Bytes>, HttpError> {
+        let (mut parts, body) = request.into_parts();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&body)?;
+        parts
+            .headers
+            .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        let request = Request::from_parts(parts, Bytes::from(encoder.finish()?));
+        self.0.send_bytes(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OtlpConfig;
+
+    fn telemetry_config(otlp: OtlpConfig) -> TelemetryConfig {
+        serde_json::from_value::<TelemetryConfig>(serde_json::json!({
+            "otel_endpoint": "http://localhost:4317",
+        }))
+        .map(|config| TelemetryConfig { otlp, ..config })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_merges_signal_config_and_env() {
+        let config = telemetry_config(OtlpConfig {
+            default: OtlpExporterConfig {
+                protocol: Some(OtlpProtocol::HttpProtobuf),
+                endpoint: Some("https://otlp.example.com/".into()),
+                headers: HashMap::from([("authorization".into(), "Bearer t".into())]),
+                compression: Some(OtlpCompression::Gzip),
+                ..Default::default()
+            },
+            logs: OtlpExporterConfig {
+                endpoint: Some("https://logs.example.com/ingest".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let no_env = |_: &str| None;
+        let traces = ExporterSettings::resolve(&config, Signal::Traces, no_env);
+        assert_eq!(traces.endpoint, "https://otlp.example.com/v1/traces");
+        assert_eq!(traces.headers["authorization"], "Bearer t");
+        assert_eq!(traces.compression, Some(OtlpCompression::Gzip));
+        assert_eq!(traces.timeout, DEFAULT_TIMEOUT);
+
+        let logs = ExporterSettings::resolve(&config, Signal::Logs, no_env);
+        assert_eq!(logs.endpoint, "https://logs.example.com/ingest");
+
+        let env = |key: &str| match key {
+            "OTEL_EXPORTER_OTLP_PROTOCOL" => Some("grpc".to_string()),
+            "OTEL_EXPORTER_OTLP_ENDPOINT" => Some("http://collector:4317".to_string()),
+            "OTEL_EXPORTER_OTLP_METRICS_TIMEOUT" => Some("3000".to_string()),
+            _ => None,
+        };
+        let metrics = ExporterSettings::resolve(&config, Signal::Metrics, env);
+        assert_eq!(metrics.protocol, OtlpProtocol::Grpc);
+        assert_eq!(metrics.endpoint, "http://collector:4317");
+        assert_eq!(metrics.timeout, Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::ffi::{CString, c_char, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pprof2::protos::{Function, Line, Location, Profile, Sample, ValueType};
+
+use super::ProfileError;
+
+/// 分配器内部的调用栈，`go tool pprof` 加载时连同其上方的帧一起隐藏
+const DROP_FRAMES: &str = ".*tikv_jemallocator.*|_rjem_.*";
+
+/// 同一进程内多次导出时区分临时文件
+static DUMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 让 jemalloc 把堆样本写入临时文件，再转换为 pprof 格式
+pub fn heap_profile() -> Result<Profile, ProfileError> {
+    // SAFETY: `opt.prof` 为 bool 类型的只读选项
+    let prof = unsafe { tikv_jemalloc_ctl::raw::read::<bool>(b"opt.prof\0") }
+        .map_err(|e| ProfileError::Failed(format!("读取 jemalloc 配置失败: {e}")))?;
+    if !prof {
+        return Err(ProfileError::Failed("jemalloc 未开启堆采样".into()));
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "axum-otel-demo-heap-{}-{}.prof",
+        std::process::id(),
+        DUMP_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| ProfileError::Failed(format!("临时文件路径无效: {e}")))?;
+    // SAFETY: `prof.dump` 接受以 NUL 结尾的文件路径，调用期间 c_path 保持有效
+    unsafe { tikv_jemalloc_ctl::raw::write::<*const c_char>(b"prof.dump\0", c_path.as_ptr()) }
+        .map_err(|e| ProfileError::Failed(format!("导出堆样本失败: {e}")))?;
+    let dump = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    let dump = dump.map_err(|e| ProfileError::Failed(format!("读取堆样本失败: {e}")))?;
+
+    let (period, samples) = parse(&dump)?;
+    Ok(build(period, &samples, symbolize))
+}
+
+/// 一个调用栈上仍存活的分配
+#[derive(Debug, PartialEq)]
+struct HeapSample {
+    /// 返回地址，栈顶在前
+    addresses: Vec<u64>,
+    objects: u64,
+    bytes: u64,
+}
+
+/// 解析 jemalloc `heap_v2` 格式，返回采样间隔（字节）与各调用栈的存活分配
+fn parse(dump: &str) -> Result<(u64, Vec<HeapSample>), ProfileError> {
+    let mut lines = dump.lines();
+    let period = lines
+        .next()
+        .and_then(|line| line.trim().strip_prefix("heap_v2/"))
+        .and_then(|period| period.parse().ok())
+        .ok_or_else(|| ProfileError::Failed("无法识别的堆样本格式".into()))?;
+
+    let mut samples = Vec::new();
+    let mut addresses = None;
+    for line in lines {
+        let line = line.trim();
+        if line == "MAPPED_LIBRARIES:" {
+            break;
+        }
+        if let Some(stack) = line.strip_prefix("@ ") {
+            addresses = Some(
+                stack
+                    .split_whitespace()
+                    .filter_map(|addr| u64::from_str_radix(addr.trim_start_matches("0x"), 16).ok())
+                    .collect(),
+            );
+        } else if let Some(counts) = line.strip_prefix("t*: ") {
+            // 首个 `t*:` 为全部调用栈的合计，前面没有 `@` 行
+            let Some(addresses) = addresses.take() else {
+                continue;
+            };
+            let mut counts = counts
+                .split([':', ' '])
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<u64>().ok());
+            if let (Some(Some(objects)), Some(Some(bytes))) = (counts.next(), counts.next())
+                && objects > 0
+            {
+                samples.push(HeapSample {
+                    addresses,
+                    objects,
+                    bytes,
+                });
+            }
+        }
+    }
+    Ok((period, samples))
+}
+
+/// 解析出的源码位置：函数名、文件名、行号
+type Frame = (String, String, i64);
+
+/// 按返回地址解析符号，内联函数展开为多个位置，最内层在前
+fn symbolize(address: u64) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    // 返回地址指向调用指令之后，减一落回调用所在行
+    backtrace::resolve(address.saturating_sub(1) as *mut c_void, |symbol| {
+        let name = symbol
+            .name()
+            .map(|name| format!("{name:#}"))
+            .unwrap_or_else(|| format!("{address:#x}"));
+        let file = symbol
+            .filename()
+            .map(|file| file.display().to_string())
+            .unwrap_or_default();
+        frames.push((name, file, symbol.lineno().map_or(0, i64::from)));
+    });
+    frames
+}
+
+/// 按采样概率还原实际分配量后生成 pprof Profile
+fn build(period: u64, samples: &[HeapSample], symbolize: impl Fn(u64) -> Vec<Frame>) -> Profile {
+    let mut builder = ProfileBuilder::default();
+    let sample_type = vec![
+        ValueType {
+            ty: builder.string("inuse_objects"),
+            unit: builder.string("count"),
+        },
+        ValueType {
+            ty: builder.string("inuse_space"),
+            unit: builder.string("bytes"),
+        },
+    ];
+    let period_type = ValueType {
+        ty: builder.string("space"),
+        unit: builder.string("bytes"),
+    };
+    let drop_frames = builder.string(DROP_FRAMES);
+
+    let sample = samples
+        .iter()
+        .map(|sample| {
+            let scale = scale(sample, period);
+            Sample {
+                location_id: sample
+                    .addresses
+                    .iter()
+                    .map(|&address| builder.location(address, &symbolize))
+                    .collect(),
+                value: vec![
+                    (sample.objects as f64 * scale).round() as i64,
+                    (sample.bytes as f64 * scale).round() as i64,
+                ],
+                label: Vec::new(),
+            }
+        })
+        .collect();
+
+    let time_nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as i64);
+    Profile {
+        sample_type,
+        sample,
+        location: builder.locations,
+        function: builder.functions,
+        string_table: builder.strings,
+        drop_frames,
+        time_nanos,
+        period_type: Some(period_type),
+        period: period as i64,
+        // 默认展示 inuse_space
+        default_sample_type: 1,
+        ..Profile::default()
+    }
+}
+
+/// 大小为 s 的分配被采样的概率为 1 - e^(-s/period)，与 jeprof 相同按其倒数放大
+fn scale(sample: &HeapSample, period: u64) -> f64 {
+    if period == 0 || sample.bytes == 0 {
+        return 1.0;
+    }
+    let average = sample.bytes as f64 / sample.objects as f64;
+    1.0 / (1.0 - (-average / period as f64).exp())
+}
+
+/// 去重字符串、函数与位置，ID 从 1 开始
+#[derive(Default)]
+struct ProfileBuilder {
+    strings: Vec<String>,
+    string_ids: HashMap<String, i64>,
+    functions: Vec<Function>,
+    function_ids: HashMap<(i64, i64), u64>,
+    locations: Vec<Location>,
+    location_ids: HashMap<u64, u64>,
+}
+
+impl ProfileBuilder {
+    fn string(&mut self, value: &str) -> i64 {
+        if self.strings.is_empty() {
+            // pprof 要求字符串表首项为空字符串
+            self.strings.push(String::new());
+            self.string_ids.insert(String::new(), 0);
+        }
+        if let Some(&id) = self.string_ids.get(value) {
+            return id;
+        }
+        let id = self.strings.len() as i64;
+        self.strings.push(value.to_owned());
+        self.string_ids.insert(value.to_owned(), id);
+        id
+    }
+
+    fn function(&mut self, name: &str, file: &str) -> u64 {
+        let key = (self.string(name), self.string(file));
+        if let Some(&id) = self.function_ids.get(&key) {
+            return id;
+        }
+        let id = self.functions.len() as u64 + 1;
+        self.functions.push(Function {
+            id,
+            name: key.0,
+            system_name: key.0,
+            filename: key.1,
+            start_line: 0,
+        });
+        self.function_ids.insert(key, id);
+        id
+    }
+
+    fn location(&mut self, address: u64, symbolize: &impl Fn(u64) -> Vec<Frame>) -> u64 {
+        if let Some(&id) = self.location_ids.get(&address) {
+            return id;
+        }
+        let line = symbolize(address)
+            .iter()
+            .map(|(name, file, line)| Line {
+                function_id: self.function(name, file),
+                line: *line,
+            })
+            .collect();
+        let id = self.locations.len() as u64 + 1;
+        self.locations.push(Location {
+            id,
+            address,
+            line,
+            ..Location::default()
+        });
+        self.location_ids.insert(address, id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "heap_v2/524288
+  t*: 3: 1572864 [0: 0]
+  t0: 3: 1572864 [0: 0]
+@ 0x10 0x20 0x30
+  t*: 2: 1048576 [0: 0]
+  t0: 2: 1048576 [0: 0]
+@ 0x10 0x40
+  t*: 1: 524288 [0: 0]
+@ 0x50
+  t*: 0: 0 [0: 0]
+
+MAPPED_LIBRARIES:
+55d4e0a00000-55d4e0b00000 r-xp 00000000 08:01 1 /usr/bin/app
+";
+
+    #[test]
+    fn test_parse_and_build_heap_profile() {
+        let (period, samples) = parse(DUMP).unwrap();
+        assert_eq!(period, 524_288);
+        assert_eq!(
+            samples,
+            vec![
+                HeapSample {
+                    addresses: vec![0x10, 0x20, 0x30],
+                    objects: 2,
+                    bytes: 1_048_576,
+                },
+                HeapSample {
+                    addresses: vec![0x10, 0x40],
+                    objects: 1,
+                    bytes: 524_288,
+                },
+            ]
+        );
+
+        let profile = build(period, &samples, |address| {
+            vec![(format!("fn_{address:x}"), "src/main.rs".into(), 1)]
+        });
+        assert_eq!(profile.location.len(), 4);
+        assert_eq!(profile.function.len(), 4);
+        assert_eq!(profile.sample[0].location_id, vec![1, 2, 3]);
+        assert_eq!(profile.sample[1].location_id, vec![1, 4]);
+        assert_eq!(profile.string_table[0], "");
+        // 平均 512 KiB 的分配被采样的概率为 1 - 1/e
+        let expected = (1_048_576.0 / (1.0 - (-1.0f64).exp())).round() as i64;
+        assert_eq!(profile.sample[0].value[1], expected);
+    }
+}
@@ -0,0 +1,125 @@
+//! 按需 profile：CPU 样本来自 pprof 采样器（进程内只能有一个，Pyroscope 运行时归其所有），
+//! 堆样本来自可选的 jemalloc 分配器
+
+#[cfg(feature = "jemalloc")]
+mod jemalloc;
+
+use std::fmt;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use pprof2::protos::{Message, Profile};
+
+use crate::config::PprofConfig;
+use crate::telemetry::runtime_metrics;
+
+/// 采样时跳过的系统库，避免信号处理栈混入结果
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+static SETTINGS: OnceLock<PprofConfig> = OnceLock::new();
+
+/// Pyroscope 已启动时 CPU 采样器归其所有，按需 CPU profile 停用
+static CPU_RESERVED: AtomicBool = AtomicBool::new(false);
+
+/// 采集失败的原因
+#[derive(Debug)]
+pub enum ProfileError {
+    /// 另一个 CPU profile 正在采集
+    Busy,
+    /// Pyroscope 持续分析占用了 CPU 采样器
+    Reserved,
+    /// 未以 `jemalloc` feature 编译，没有堆样本
+    #[cfg_attr(feature = "jemalloc", allow(dead_code))]
+    Unsupported,
+    Failed(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Busy => f.write_str("另一个 CPU profile 正在采集"),
+            ProfileError::Reserved => f.write_str(
+                "Pyroscope 持续分析占用了 CPU 采样器，按需 CPU profile 需设置 telemetry.profiling.enabled = false",
+            ),
+            ProfileError::Unsupported => f.write_str("未启用 jemalloc，无法采集堆 profile"),
+            ProfileError::Failed(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// 按配置启用按需 profile
+pub fn init(config: &PprofConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+/// Pyroscope 启动后调用，此后按需 CPU profile 直接拒绝；堆 profile 不受影响
+pub fn reserve_cpu_for_pyroscope() {
+    CPU_RESERVED.store(true, Ordering::Relaxed);
+}
+
+/// 未启用时返回 None
+pub fn settings() -> Option<&'static PprofConfig> {
+    SETTINGS.get().filter(|settings| settings.enabled)
+}
+
+/// 采集 `duration` 内的 CPU 样本，返回 gzip 压缩的 pprof protobuf
+pub async fn cpu_profile(duration: Duration, sample_rate: i32) -> Result<Vec<u8>, ProfileError> {
+    if CPU_RESERVED.load(Ordering::Relaxed) {
+        return Err(ProfileError::Reserved);
+    }
+    let guard = pprof2::ProfilerGuardBuilder::default()
+        .frequency(sample_rate)
+        .blocklist(&BLOCKLIST)
+        .build()
+        .map_err(|e| match e {
+            pprof2::Error::Running => ProfileError::Busy,
+            e => ProfileError::Failed(format!("启动 CPU 采样失败: {e}")),
+        })?;
+    tokio::time::sleep(duration).await;
+    // 生成报告要解析全部调用栈的符号，放到阻塞线程池
+    blocking(move || {
+        let profile = guard
+            .report()
+            .build()
+            .and_then(|report| report.pprof())
+            .map_err(|e| ProfileError::Failed(format!("生成 CPU profile 失败: {e}")))?;
+        drop(guard);
+        encode(&profile)
+    })
+    .await
+}
+
+/// 当前存活内存的分配调用栈，返回 gzip 压缩的 pprof protobuf
+pub async fn heap_profile() -> Result<Vec<u8>, ProfileError> {
+    #[cfg(feature = "jemalloc")]
+    {
+        // 导出堆样本要读写临时文件，首次符号解析还会加载调试信息
+        blocking(|| encode(&jemalloc::heap_profile()?)).await
+    }
+    #[cfg(not(feature = "jemalloc"))]
+    {
+        Err(ProfileError::Unsupported)
+    }
+}
+
+async fn blocking<F>(f: F) -> Result<Vec<u8>, ProfileError>
+where
+    F: FnOnce() -> Result<Vec<u8>, ProfileError> + Send + 'static,
+{
+    runtime_metrics::spawn_blocking(f)
+        .await
+        .map_err(|e| ProfileError::Failed(format!("profile 任务异常退出: {e}")))?
+}
+
+/// `go tool pprof` 约定 profile 以 gzip 压缩传输
+fn encode(profile: &Profile) -> Result<Vec<u8>, ProfileError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&profile.encode_to_vec())
+        .and_then(|_| encoder.finish())
+        .map_err(|e| ProfileError::Failed(format!("压缩 profile 失败: {e}")))
+}
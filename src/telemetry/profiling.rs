@@ -1,15 +1,265 @@
-use pyroscope::PyroscopeAgent;
-use pyroscope::pyroscope::PyroscopeAgentReady;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use pyroscope::pyroscope::PyroscopeAgentRunning;
+use pyroscope::{PyroscopeAgent, PyroscopeError};
 use pyroscope_pprofrs::{pprof_backend, PprofConfig};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_opentelemetry::OtelData;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::config::ProfilingConfig;
+
+/// 初始化并启动 Pyroscope Agent（CPU profiling），应用名、标签与采样频率取自配置
+pub fn init_pyroscope(
+    config: &ProfilingConfig,
+) -> Result<PyroscopeAgent<PyroscopeAgentRunning>, PyroscopeError> {
+    let mut tags: Vec<(&str, &str)> = config
+        .tags
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    if !config.tags.contains_key("version") {
+        tags.push(("version", env!("CARGO_PKG_VERSION")));
+    }
+
+    PyroscopeAgent::builder(config.endpoint.as_str(), config.application_name.as_str())
+        .tags(tags)
+        .backend(pprof_backend(
+            PprofConfig::new().sample_rate(config.sample_rate),
+        ))
+        .build()?
+        .start()
+}
+
+type TagFn = Box<dyn Fn(String, String) -> pyroscope::Result<()> + Send + Sync>;
+
+/// 请求 span 的 profile 标签，子 span 继承所属请求的标签
+#[derive(Debug)]
+struct ProfileLabels {
+    /// 请求 span 的 tracing ID，该 span 关闭时撤下标签
+    owner: Id,
+    span_id: String,
+    route: Option<String>,
+}
+
+thread_local! {
+    /// 当前线程已进入的 span 对应的标签
+    static ACTIVE: RefCell<Vec<Option<Arc<ProfileLabels>>>> = const { RefCell::new(Vec::new()) };
+    /// 当前线程已写入 Pyroscope 的标签
+    static APPLIED: RefCell<Option<Arc<ProfileLabels>>> = const { RefCell::new(None) };
+}
+
+/// 在请求 span 活跃期间为当前线程的采样打上 `span_id` / `route` 标签
+///
+/// 请求 future 每次被轮询都会进出一次请求 span，而写标签要争用 agent 的全局锁，
+/// 因此线程退出所有 span 后保留标签，直到进入其他请求或无标签的 span、或请求 span 关闭时才撤下；
+/// 同一请求在同一线程上的连续轮询不触发任何标签操作
+///
+/// 需放在 OpenTelemetry 层之后，以便读取请求 span 的 span_id
+pub struct SpanLabelsLayer {
+    add: TagFn,
+    remove: TagFn,
+}
+
+impl SpanLabelsLayer {
+    pub fn new(agent: &PyroscopeAgent<PyroscopeAgentRunning>) -> Self {
+        let (add, remove) = agent.tag_wrapper();
+        Self::with_tags(Box::new(add), Box::new(remove))
+    }
+
+    fn with_tags(add: TagFn, remove: TagFn) -> Self {
+        Self { add, remove }
+    }
+
+    /// 让当前线程的标签变为 `to`；与已写入的标签相同时不做任何操作
+    fn apply(&self, to: Option<&Arc<ProfileLabels>>) {
+        APPLIED.with(|applied| {
+            let mut applied = applied.borrow_mut();
+            let same = match (applied.as_ref(), to) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            };
+            if same {
+                return;
+            }
+            // agent 已停止时标签操作会失败，忽略即可
+            if let Some(labels) = applied.take() {
+                let _ = (self.remove)("span_id".into(), labels.span_id.clone());
+                if let Some(route) = &labels.route {
+                    let _ = (self.remove)("route".into(), route.clone());
+                }
+            }
+            if let Some(labels) = to {
+                let _ = (self.add)("span_id".into(), labels.span_id.clone());
+                if let Some(route) = &labels.route {
+                    let _ = (self.add)("route".into(), route.clone());
+                }
+            }
+            *applied = to.cloned();
+        });
+    }
+}
+
+impl<S> Layer<S> for SpanLabelsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        // 带 `http.route` 字段的是请求 span，其余 span 沿用父级的标签
+        let labels = if attrs.metadata().fields().field("http.route").is_some() {
+            let span_id = span
+                .extensions()
+                .get::<OtelData>()
+                .and_then(|data| data.builder.span_id);
+            span_id.map(|span_id| {
+                let mut route = RouteVisitor(None);
+                attrs.record(&mut route);
+                Arc::new(ProfileLabels {
+                    owner: id.clone(),
+                    span_id: span_id.to_string(),
+                    route: route.0,
+                })
+            })
+        } else {
+            span.parent()
+                .and_then(|parent| parent.extensions().get::<Arc<ProfileLabels>>().cloned())
+        };
+
+        if let Some(labels) = labels {
+            span.extensions_mut().insert(labels);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let labels = ctx
+            .span(id)
+            .and_then(|span| span.extensions().get::<Arc<ProfileLabels>>().cloned());
+        self.apply(labels.as_ref());
+        ACTIVE.with(|active| active.borrow_mut().push(labels));
+    }
+
+    fn on_exit(&self, _id: &Id, _ctx: Context<'_, S>) {
+        let next = ACTIVE.with(|active| {
+            let mut active = active.borrow_mut();
+            active.pop();
+            active.last().cloned()
+        });
+        // 退出最外层 span 时保留标签，等下一次进入 span 再决定是否切换
+        if let Some(next) = next {
+            self.apply(next.as_ref());
+        }
+    }
+
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        let owned = APPLIED.with(|applied| {
+            applied
+                .borrow()
+                .as_ref()
+                .is_some_and(|labels| labels.owner == id)
+        });
+        if owned {
+            self.apply(None);
+        }
+    }
+}
+
+struct RouteVisitor(Option<String>);
+
+impl Visit for RouteVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "http.route" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "http.route" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    type Calls = Arc<Mutex<Vec<(&'static str, String, String)>>>;
+
+    fn recorder(calls: &Calls, op: &'static str) -> TagFn {
+        let calls = calls.clone();
+        Box::new(move |key, value| {
+            calls.lock().unwrap().push((op, key, value));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_request_span_labels() {
+        let calls = Calls::default();
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+            .with(SpanLabelsLayer::with_tags(
+                recorder(&calls, "add"),
+                recorder(&calls, "remove"),
+            ));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request", http.route = "/api/items");
+            let span_id = request
+                .with_subscriber(|(id, dispatch)| {
+                    let registry = dispatch.downcast_ref::<tracing_subscriber::Registry>()?;
+                    let span = registry.span(id)?;
+                    let labels = span.extensions().get::<Arc<ProfileLabels>>()?.clone();
+                    Some(labels.span_id.clone())
+                })
+                .flatten()
+                .expect("request span has labels");
+            let expected = vec![
+                ("add", "span_id".to_owned(), span_id.clone()),
+                ("add", "route".to_owned(), "/api/items".to_owned()),
+            ];
+
+            // 多次轮询同一请求、进入子 span 都不重复写标签
+            for _ in 0..3 {
+                let _poll = request.enter();
+                let _child = tracing::info_span!("query").entered();
+            }
+            assert_eq!(*calls.lock().unwrap(), expected);
+
+            // 其他无标签的 span 占用线程时撤下标签
+            tracing::info_span!("background").in_scope(|| {});
+            assert_eq!(calls.lock().unwrap().len(), 4);
+            assert_eq!(
+                calls.lock().unwrap()[2..],
+                [
+                    ("remove", "span_id".to_owned(), span_id.clone()),
+                    ("remove", "route".to_owned(), "/api/items".to_owned()),
+                ]
+            );
 
-/// 初始化 Pyroscope Agent（CPU profiling，100Hz 采样）
-pub fn init_pyroscope(endpoint: &str) -> PyroscopeAgent<PyroscopeAgentReady> {
-    PyroscopeAgent::builder(endpoint, "axum-otel-demo")
-        .tags(vec![
-            ("version", env!("CARGO_PKG_VERSION")),
-            ("env", "development"),
-        ])
-        .backend(pprof_backend(PprofConfig::new().sample_rate(100)))
-        .build()
-        .expect("Failed to create Pyroscope agent")
+            // 请求 span 关闭时撤下本线程上仍生效的标签
+            calls.lock().unwrap().clear();
+            request.in_scope(|| {});
+            drop(request);
+            assert_eq!(calls.lock().unwrap().len(), 4);
+            assert_eq!(calls.lock().unwrap()[3].0, "remove");
+        });
+    }
 }
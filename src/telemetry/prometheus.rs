@@ -0,0 +1,609 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Weak};
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use opentelemetry::{KeyValue, Value};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::metrics::data::{Gauge, Histogram, Metric, ResourceMetrics, Sum};
+use opentelemetry_sdk::metrics::reader::MetricReader;
+use opentelemetry_sdk::metrics::{
+    InstrumentKind, ManualReader, MetricResult, Pipeline, Temporality,
+};
+
+const CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prometheus 拉取式导出器：持有 ManualReader，抓取时现场 collect 并编码为文本格式
+#[derive(Clone, Debug)]
+pub struct PrometheusExporter {
+    reader: Arc<ManualReader>,
+}
+
+/// 注册到 MeterProvider 的 reader，与 `PrometheusExporter` 共享同一个 ManualReader
+#[derive(Debug)]
+pub(super) struct SharedReader(pub(super) Arc<ManualReader>);
+
+impl MetricReader for SharedReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.0.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.0.shutdown()
+    }
+
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
+
+impl PrometheusExporter {
+    pub fn new() -> Self {
+        Self {
+            reader: Arc::new(ManualReader::builder().build()),
+        }
+    }
+
+    /// 供 MeterProvider 注册的 reader
+    pub(super) fn reader(&self) -> SharedReader {
+        SharedReader(self.reader.clone())
+    }
+
+    /// 采集当前全部指标并编码为 Prometheus 文本格式
+    pub fn render(&self) -> String {
+        let mut rm = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+        if let Err(e) = self.reader.collect(&mut rm) {
+            tracing::warn!(error = %e, "Failed to collect metrics for Prometheus");
+            return String::new();
+        }
+        encode(&rm)
+    }
+
+    /// `GET /metrics` 抓取端点
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/metrics", get(scrape))
+            .with_state(self.clone())
+    }
+
+    /// 在独立地址上提供 `/metrics`
+    pub async fn serve(self, addr: String) {
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(error = %e, addr = %addr, "Failed to bind Prometheus listener");
+                return;
+            }
+        };
+        tracing::info!("Prometheus metrics listening on {}/metrics", addr);
+        if let Err(e) = axum::serve(listener, self.router()).await {
+            tracing::error!(error = %e, "Prometheus listener stopped");
+        }
+    }
+}
+
+async fn scrape(State(exporter): State<PrometheusExporter>) -> impl IntoResponse {
+    ([(CONTENT_TYPE, CONTENT_TYPE_TEXT)], exporter.render())
+}
+
+/// 同名指标的 HELP/TYPE 与全部样本；文本格式要求同名样本连续且只声明一次
+struct Family {
+    help: String,
+    kind: &'static str,
+    samples: String,
+}
+
+/// 按 Prometheus 指标名归并多个 scope 的样本，保持首次出现的顺序
+#[derive(Default)]
+struct Families {
+    order: Vec<Family>,
+    index: HashMap<String, usize>,
+}
+
+impl Families {
+    /// 取得指标名对应的样本缓冲区，HELP/TYPE 以首个 scope 为准；类型冲突时跳过该指标
+    fn samples(
+        &mut self,
+        name: &str,
+        description: &str,
+        kind: &'static str,
+    ) -> Option<&mut String> {
+        let i = match self.index.get(name) {
+            Some(&i) => i,
+            None => {
+                let mut help = String::new();
+                write_header(&mut help, name, description, kind);
+                self.order.push(Family {
+                    help,
+                    kind,
+                    samples: String::new(),
+                });
+                self.index.insert(name.to_owned(), self.order.len() - 1);
+                self.order.len() - 1
+            }
+        };
+        let family = &mut self.order[i];
+        if family.kind != kind {
+            tracing::warn!(
+                metric = name,
+                "Metric exported with conflicting types across scopes, skipped"
+            );
+            return None;
+        }
+        Some(&mut family.samples)
+    }
+}
+
+/// 将一次 collect 的结果编码为 Prometheus 文本格式
+fn encode(rm: &ResourceMetrics) -> String {
+    let mut out = String::new();
+
+    // Resource 属性以 target_info 暴露，与 OTel → Prometheus 兼容规范一致
+    let resource_labels: Vec<KeyValue> = rm
+        .resource
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .collect();
+    if !resource_labels.is_empty() {
+        let _ = writeln!(out, "# HELP target_info Target metadata");
+        let _ = writeln!(out, "# TYPE target_info gauge");
+        let _ = writeln!(out, "target_info{} 1", labels(&resource_labels, None));
+    }
+
+    // 每个样本带上 otel_scope_name/otel_scope_version，不同 scope 的同名同属性样本不会成为重复序列
+    let mut families = Families::default();
+    for scope in &rm.scope_metrics {
+        let mut scope_labels = vec![KeyValue::new(
+            "otel_scope_name",
+            scope.scope.name().to_owned(),
+        )];
+        if let Some(version) = scope.scope.version() {
+            scope_labels.push(KeyValue::new("otel_scope_version", version.to_owned()));
+        }
+        for metric in &scope.metrics {
+            encode_metric(&mut families, &scope_labels, metric);
+        }
+    }
+    for family in families.order {
+        out.push_str(&family.help);
+        out.push_str(&family.samples);
+    }
+    out
+}
+
+fn encode_metric(families: &mut Families, scope: &[KeyValue], metric: &Metric) {
+    let data = metric.data.as_any();
+    let base = metric_name(&metric.name, &metric.unit);
+
+    if let Some(hist) = data.downcast_ref::<Histogram<f64>>() {
+        let Some(out) = families.samples(&base, &metric.description, "histogram") else {
+            return;
+        };
+        for dp in &hist.data_points {
+            write_histogram(
+                out,
+                &base,
+                &scoped(&dp.attributes, scope),
+                &dp.bounds,
+                &dp.bucket_counts,
+                dp.sum,
+                dp.count,
+            );
+        }
+    } else if let Some(hist) = data.downcast_ref::<Histogram<u64>>() {
+        let Some(out) = families.samples(&base, &metric.description, "histogram") else {
+            return;
+        };
+        for dp in &hist.data_points {
+            write_histogram(
+                out,
+                &base,
+                &scoped(&dp.attributes, scope),
+                &dp.bounds,
+                &dp.bucket_counts,
+                dp.sum as f64,
+                dp.count,
+            );
+        }
+    } else if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+        encode_sum(
+            families,
+            &base,
+            scope,
+            metric,
+            sum.is_monotonic,
+            sum.data_points
+                .iter()
+                .map(|dp| (&dp.attributes, dp.value as f64)),
+        );
+    } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+        encode_sum(
+            families,
+            &base,
+            scope,
+            metric,
+            sum.is_monotonic,
+            sum.data_points
+                .iter()
+                .map(|dp| (&dp.attributes, dp.value as f64)),
+        );
+    } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+        encode_sum(
+            families,
+            &base,
+            scope,
+            metric,
+            sum.is_monotonic,
+            sum.data_points.iter().map(|dp| (&dp.attributes, dp.value)),
+        );
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+        let Some(out) = families.samples(&base, &metric.description, "gauge") else {
+            return;
+        };
+        for dp in &gauge.data_points {
+            write_sample(
+                out,
+                &base,
+                &scoped(&dp.attributes, scope),
+                None,
+                dp.value as f64,
+            );
+        }
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+        let Some(out) = families.samples(&base, &metric.description, "gauge") else {
+            return;
+        };
+        for dp in &gauge.data_points {
+            write_sample(
+                out,
+                &base,
+                &scoped(&dp.attributes, scope),
+                None,
+                dp.value as f64,
+            );
+        }
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+        let Some(out) = families.samples(&base, &metric.description, "gauge") else {
+            return;
+        };
+        for dp in &gauge.data_points {
+            write_sample(out, &base, &scoped(&dp.attributes, scope), None, dp.value);
+        }
+    }
+}
+
+fn encode_sum<'a>(
+    families: &mut Families,
+    base: &str,
+    scope: &[KeyValue],
+    metric: &Metric,
+    is_monotonic: bool,
+    points: impl Iterator<Item = (&'a Vec<KeyValue>, f64)>,
+) {
+    let (name, kind) = if is_monotonic {
+        (format!("{base}_total"), "counter")
+    } else {
+        (base.to_owned(), "gauge")
+    };
+    let Some(out) = families.samples(&name, &metric.description, kind) else {
+        return;
+    };
+    for (attributes, value) in points {
+        write_sample(out, &name, &scoped(attributes, scope), None, value);
+    }
+}
+
+/// 数据点属性追加 scope 标签
+fn scoped(attributes: &[KeyValue], scope: &[KeyValue]) -> Vec<KeyValue> {
+    attributes.iter().chain(scope).cloned().collect()
+}
+
+fn write_header(out: &mut String, name: &str, description: &str, kind: &str) {
+    if !description.is_empty() {
+        let _ = writeln!(
+            out,
+            "# HELP {name} {}",
+            description.replace('\\', "\\\\").replace('\n', "\\n")
+        );
+    }
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn write_histogram(
+    out: &mut String,
+    base: &str,
+    attributes: &[KeyValue],
+    bounds: &[f64],
+    bucket_counts: &[u64],
+    sum: f64,
+    count: u64,
+) {
+    let bucket = format!("{base}_bucket");
+    let mut cumulative = 0;
+    for (i, bound) in bounds.iter().enumerate() {
+        cumulative += bucket_counts.get(i).copied().unwrap_or(0);
+        write_sample(
+            out,
+            &bucket,
+            attributes,
+            Some(&bound.to_string()),
+            cumulative as f64,
+        );
+    }
+    write_sample(out, &bucket, attributes, Some("+Inf"), count as f64);
+    write_sample(out, &format!("{base}_sum"), attributes, None, sum);
+    write_sample(
+        out,
+        &format!("{base}_count"),
+        attributes,
+        None,
+        count as f64,
+    );
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    attributes: &[KeyValue],
+    le: Option<&str>,
+    value: f64,
+) {
+    let _ = writeln!(out, "{name}{} {value}", labels(attributes, le));
+}
+
+fn labels(attributes: &[KeyValue], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = attributes
+        .iter()
+        .map(|kv| {
+            format!(
+                "{}=\"{}\"",
+                sanitize(kv.key.as_str()),
+                escape_value(&kv.value)
+            )
+        })
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape_value(value: &Value) -> String {
+    value
+        .as_str()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// OTel 指标名 → Prometheus 指标名：非法字符替换为 `_` 并追加单位后缀
+fn metric_name(name: &str, unit: &str) -> String {
+    let mut name = sanitize(name);
+    let suffix = match unit {
+        "s" => "seconds",
+        "ms" => "milliseconds",
+        "By" => "bytes",
+        "1" => "ratio",
+        _ => "",
+    };
+    if !suffix.is_empty() && !name.ends_with(suffix) {
+        name.push('_');
+        name.push_str(suffix);
+    }
+    name
+}
+
+fn sanitize(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use opentelemetry::InstrumentationScope;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use tower::ServiceExt;
+
+    use crate::app::{self, AppState};
+    use crate::telemetry::metrics;
+
+    fn provider(exporter: &PrometheusExporter) -> SdkMeterProvider {
+        SdkMeterProvider::builder()
+            .with_reader(exporter.reader())
+            .with_resource(
+                Resource::builder_empty()
+                    .with_attribute(KeyValue::new("service.name", "prometheus-test"))
+                    .build(),
+            )
+            .build()
+    }
+
+    async fn scrape(router: Router) -> String {
+        let response = router
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_TEXT);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scrape_http_server_metrics() {
+        let installed = metrics::testing::install().await;
+        let exporter = PrometheusExporter {
+            reader: installed.reader().0,
+        };
+
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+        let router = app::create_router(AppState { db }).merge(exporter.router());
+
+        for (uri, status) in [
+            ("/health/ready", StatusCode::OK),
+            ("/health/ready", StatusCode::OK),
+            ("/api/admin/news/7", StatusCode::UNAUTHORIZED),
+        ] {
+            let response = router
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{uri}");
+            // 消费完 body，触发响应体大小的记录
+            response.into_body().collect().await.unwrap();
+        }
+
+        let text = scrape(router).await;
+
+        assert_eq!(
+            text.matches("# TYPE http_server_request_duration_seconds histogram")
+                .count(),
+            1
+        );
+        assert_eq!(
+            text.matches("# TYPE http_server_active_requests gauge")
+                .count(),
+            1
+        );
+        assert_eq!(
+            text.matches("# TYPE http_server_response_body_size_bytes histogram")
+                .count(),
+            1
+        );
+
+        let count = |route: &str, status: u16| {
+            text.lines()
+                .find(|line| {
+                    line.starts_with("http_server_request_duration_seconds_count{")
+                        && line.contains(&format!("http_route=\"{route}\""))
+                        && line.contains(&format!("http_response_status_code=\"{status}\""))
+                })
+                .unwrap_or_else(|| panic!("no series for {route} {status}:\n{text}"))
+                .to_owned()
+        };
+        let ready = count("/health/ready", 200);
+        assert!(ready.contains("http_request_method=\"GET\""));
+        assert!(ready.contains("url_scheme=\"http\""));
+        assert!(ready.contains("otel_scope_name=\"axum-otel-demo\""));
+        assert!(ready.ends_with(" 2"), "{ready}");
+        // 路由模板而不是原始路径，避免 ID 成为高基数标签
+        assert!(count("/api/admin/news/{id}", 401).ends_with(" 1"));
+        assert!(!text.contains("/api/admin/news/7"));
+
+        // 请求全部结束后活跃请求数回到 0
+        assert!(
+            text.lines()
+                .filter(|line| line.starts_with("http_server_active_requests{"))
+                .all(|line| line.ends_with(" 0"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_merges_scopes() {
+        let exporter = PrometheusExporter::new();
+        let provider = provider(&exporter);
+
+        // 两个 scope 导出同名、同属性的指标：HELP/TYPE 只出现一次，样本以 scope 标签区分
+        for scope in ["scope-a", "scope-b"] {
+            let meter = provider.meter_with_scope(
+                InstrumentationScope::builder(scope)
+                    .with_version("1.0")
+                    .build(),
+            );
+            meter
+                .u64_counter("jobs")
+                .with_description("Processed jobs.")
+                .build()
+                .add(2, &[KeyValue::new("source", "queue")]);
+            let duration = meter
+                .f64_histogram("job.duration")
+                .with_unit("s")
+                .with_boundaries(vec![0.1, 1.0])
+                .build();
+            for value in [0.05, 0.5, 5.0] {
+                duration.record(value, &[KeyValue::new("source", "queue")]);
+            }
+        }
+
+        let text = scrape(exporter.router()).await;
+
+        assert!(text.contains("target_info{service_name=\"prometheus-test\"} 1"));
+        assert_eq!(text.matches("# TYPE jobs_total counter").count(), 1);
+        assert_eq!(text.matches("# HELP jobs_total ").count(), 1);
+        assert_eq!(
+            text.matches("# TYPE job_duration_seconds histogram")
+                .count(),
+            1
+        );
+        for scope in ["scope-a", "scope-b"] {
+            let labels =
+                format!("source=\"queue\",otel_scope_name=\"{scope}\",otel_scope_version=\"1.0\"");
+            assert!(text.contains(&format!("jobs_total{{{labels}}} 2")));
+            assert!(text.contains(&format!(
+                "job_duration_seconds_bucket{{{labels},le=\"1\"}} 2"
+            )));
+            assert!(text.contains(&format!(
+                "job_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 3"
+            )));
+        }
+
+        // 不能出现重复序列（同名且标签完全相同的样本）
+        let mut series: Vec<&str> = text
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .filter_map(|l| l.rsplit_once(' ').map(|(series, _)| series))
+            .collect();
+        let total = series.len();
+        series.sort_unstable();
+        series.dedup();
+        assert_eq!(series.len(), total);
+
+        // 同名样本须紧跟在各自的 TYPE 行之后
+        let lines: Vec<&str> = text.lines().collect();
+        let type_at = lines
+            .iter()
+            .position(|l| *l == "# TYPE jobs_total counter")
+            .unwrap();
+        assert!(lines[type_at + 1].starts_with("jobs_total{"));
+        assert!(lines[type_at + 2].starts_with("jobs_total{"));
+    }
+}
@@ -0,0 +1,287 @@
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::propagation::text_map_propagator::FieldIter;
+use opentelemetry::propagation::{
+    Extractor, Injector, TextMapCompositePropagator, TextMapPropagator,
+};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+
+use crate::config::Propagator;
+
+/// 按配置顺序组装全局 TextMapPropagator，入站请求按此顺序提取上下文
+pub fn init_propagator(propagators: &[Propagator]) {
+    let propagators: Vec<Box<dyn TextMapPropagator + Send + Sync>> = propagators
+        .iter()
+        .map(|p| -> Box<dyn TextMapPropagator + Send + Sync> {
+            match p {
+                Propagator::TraceContext => Box::new(TraceContextPropagator::new()),
+                Propagator::Baggage => Box::new(BaggagePropagator::new()),
+                Propagator::B3 => Box::new(B3Propagator::single()),
+                Propagator::B3Multi => Box::new(B3Propagator::multi()),
+                Propagator::Jaeger => Box::new(JaegerPropagator::new()),
+            }
+        })
+        .collect();
+
+    opentelemetry::global::set_text_map_propagator(TextMapCompositePropagator::new(propagators));
+}
+
+const B3_SINGLE_HEADER: &str = "b3";
+const B3_TRACE_ID_HEADER: &str = "x-b3-traceid";
+const B3_SPAN_ID_HEADER: &str = "x-b3-spanid";
+const B3_SAMPLED_HEADER: &str = "x-b3-sampled";
+const B3_FLAGS_HEADER: &str = "x-b3-flags";
+
+/// Zipkin B3 传播格式，提取时单头与多头都接受，注入时按构造方式二选一
+#[derive(Debug)]
+pub struct B3Propagator {
+    single_header: bool,
+    fields: Vec<String>,
+}
+
+impl B3Propagator {
+    /// 注入 `b3: {trace_id}-{span_id}-{sampled}`
+    pub fn single() -> Self {
+        Self {
+            single_header: true,
+            fields: vec![B3_SINGLE_HEADER.to_string()],
+        }
+    }
+
+    /// 注入 `X-B3-TraceId` / `X-B3-SpanId` / `X-B3-Sampled`
+    pub fn multi() -> Self {
+        Self {
+            single_header: false,
+            fields: vec![
+                B3_TRACE_ID_HEADER.to_string(),
+                B3_SPAN_ID_HEADER.to_string(),
+                B3_SAMPLED_HEADER.to_string(),
+            ],
+        }
+    }
+
+    fn extract_single(&self, value: &str) -> Option<SpanContext> {
+        let mut parts = value.trim().split('-');
+        let trace_id = parse_trace_id(parts.next()?)?;
+        let span_id = SpanId::from_hex(parts.next()?).ok()?;
+        let flags = match parts.next() {
+            Some("1") | Some("d") => TraceFlags::SAMPLED,
+            _ => TraceFlags::NOT_SAMPLED,
+        };
+        valid_remote(trace_id, span_id, flags)
+    }
+
+    fn extract_multi(&self, extractor: &dyn Extractor) -> Option<SpanContext> {
+        let trace_id = parse_trace_id(extractor.get(B3_TRACE_ID_HEADER)?)?;
+        let span_id = SpanId::from_hex(extractor.get(B3_SPAN_ID_HEADER)?.trim()).ok()?;
+        let debug = extractor.get(B3_FLAGS_HEADER) == Some("1");
+        let sampled = matches!(extractor.get(B3_SAMPLED_HEADER), Some("1") | Some("true"));
+        let flags = if debug || sampled {
+            TraceFlags::SAMPLED
+        } else {
+            TraceFlags::NOT_SAMPLED
+        };
+        valid_remote(trace_id, span_id, flags)
+    }
+}
+
+impl TextMapPropagator for B3Propagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let sc = span.span_context();
+        if !sc.is_valid() {
+            return;
+        }
+        let sampled = if sc.is_sampled() { "1" } else { "0" };
+        if self.single_header {
+            injector.set(
+                B3_SINGLE_HEADER,
+                format!("{}-{}-{}", sc.trace_id(), sc.span_id(), sampled),
+            );
+        } else {
+            injector.set(B3_TRACE_ID_HEADER, sc.trace_id().to_string());
+            injector.set(B3_SPAN_ID_HEADER, sc.span_id().to_string());
+            injector.set(B3_SAMPLED_HEADER, sampled.to_string());
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let extracted = extractor
+            .get(B3_SINGLE_HEADER)
+            .and_then(|v| self.extract_single(v))
+            .or_else(|| self.extract_multi(extractor));
+
+        match extracted {
+            Some(sc) => cx.with_remote_span_context(sc),
+            None => cx.clone(),
+        }
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+const JAEGER_HEADER: &str = "uber-trace-id";
+const JAEGER_BAGGAGE_PREFIX: &str = "uberctx-";
+
+/// Jaeger `uber-trace-id: {trace_id}:{span_id}:{parent_span_id}:{flags}` 传播格式，
+/// 同时把 `uberctx-*` 头提取为 baggage
+#[derive(Debug)]
+pub struct JaegerPropagator {
+    fields: Vec<String>,
+}
+
+impl JaegerPropagator {
+    pub fn new() -> Self {
+        Self {
+            fields: vec![JAEGER_HEADER.to_string()],
+        }
+    }
+
+    fn extract_span_context(&self, extractor: &dyn Extractor) -> Option<SpanContext> {
+        // 值可能经过 URL 编码（`:` → `%3A`）
+        let value = extractor.get(JAEGER_HEADER)?.replace("%3A", ":");
+        let parts: Vec<&str> = value.trim().split(':').collect();
+        if parts.len() != 4 {
+            return None;
+        }
+        let trace_id = parse_trace_id(parts[0])?;
+        let span_id = SpanId::from_hex(parts[1]).ok()?;
+        let flags = u8::from_str_radix(parts[3], 16).ok()?;
+        // bit 0: sampled，bit 1: debug（debug 视同采样）
+        let flags = if flags & 0x03 != 0 {
+            TraceFlags::SAMPLED
+        } else {
+            TraceFlags::NOT_SAMPLED
+        };
+        valid_remote(trace_id, span_id, flags)
+    }
+}
+
+impl TextMapPropagator for JaegerPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let sc = span.span_context();
+        if !sc.is_valid() {
+            return;
+        }
+        let flags = if sc.is_sampled() { "1" } else { "0" };
+        injector.set(
+            JAEGER_HEADER,
+            format!("{}:{}:0:{}", sc.trace_id(), sc.span_id(), flags),
+        );
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let mut cx = match self.extract_span_context(extractor) {
+            Some(sc) => cx.with_remote_span_context(sc),
+            None => cx.clone(),
+        };
+
+        let baggage: Vec<KeyValue> = extractor
+            .keys()
+            .into_iter()
+            .filter_map(|key| {
+                let name = key.strip_prefix(JAEGER_BAGGAGE_PREFIX)?;
+                let value = extractor.get(key)?;
+                Some(KeyValue::new(name.to_string(), value.to_string()))
+            })
+            .collect();
+        if !baggage.is_empty() {
+            let merged: Vec<KeyValue> = cx
+                .baggage()
+                .iter()
+                .map(|(k, (v, _))| KeyValue::new(k.clone(), v.clone()))
+                .chain(baggage)
+                .collect();
+            cx = cx.with_baggage(merged);
+        }
+        cx
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+/// 解析 16 或 32 位十六进制 trace id（64 位 id 左侧补零）
+fn parse_trace_id(hex: &str) -> Option<TraceId> {
+    let hex = hex.trim();
+    if hex.is_empty() || hex.len() > 32 {
+        return None;
+    }
+    TraceId::from_hex(hex).ok()
+}
+
+fn valid_remote(trace_id: TraceId, span_id: SpanId, flags: TraceFlags) -> Option<SpanContext> {
+    let sc = SpanContext::new(trace_id, span_id, flags, true, TraceState::default());
+    sc.is_valid().then_some(sc)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn extract(propagator: &dyn TextMapPropagator, headers: &[(&str, &str)]) -> Context {
+        let carrier: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        propagator.extract_with_context(&Context::new(), &carrier)
+    }
+
+    #[test]
+    fn test_b3_single_and_multi() {
+        let cx = extract(
+            &B3Propagator::single(),
+            &[("b3", "80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-1")],
+        );
+        let sc = cx.span().span_context().clone();
+        assert_eq!(
+            sc.trace_id().to_string(),
+            "80f198ee56343ba864fe8b2a57d3eff7"
+        );
+        assert_eq!(sc.span_id().to_string(), "e457b5a2e4d86bd1");
+        assert!(sc.is_sampled() && sc.is_remote());
+
+        let cx = extract(
+            &B3Propagator::single(),
+            &[
+                ("x-b3-traceid", "64fe8b2a57d3eff7"),
+                ("x-b3-spanid", "e457b5a2e4d86bd1"),
+                ("x-b3-sampled", "0"),
+            ],
+        );
+        let sc = cx.span().span_context().clone();
+        assert_eq!(
+            sc.trace_id().to_string(),
+            "000000000000000064fe8b2a57d3eff7"
+        );
+        assert!(!sc.is_sampled());
+    }
+
+    #[test]
+    fn test_jaeger_with_baggage() {
+        let cx = extract(
+            &JaegerPropagator::new(),
+            &[
+                (
+                    "uber-trace-id",
+                    "80f198ee56343ba864fe8b2a57d3eff7%3Ae457b5a2e4d86bd1%3A0%3A1",
+                ),
+                ("uberctx-tenant", "acme"),
+            ],
+        );
+        let sc = cx.span().span_context().clone();
+        assert_eq!(sc.span_id().to_string(), "e457b5a2e4d86bd1");
+        assert!(sc.is_sampled());
+        assert_eq!(
+            cx.baggage().get("tenant").map(|v| v.as_str().to_string()),
+            Some("acme".into())
+        );
+    }
+}
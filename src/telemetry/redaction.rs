@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use crate::config::RedactionConfig;
+
+static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+
+/// 按配置初始化全局脱敏器
+pub fn init(config: &RedactionConfig) {
+    let _ = REDACTOR.set(Redactor::new(config));
+}
+
+/// 获取全局脱敏器，未初始化时使用默认规则
+pub fn redactor() -> &'static Redactor {
+    REDACTOR.get_or_init(|| Redactor::new(&RedactionConfig::default()))
+}
+
+/// 日志脱敏器：按字段名 / JSON 路径遮盖 body 中的敏感值，按名称遮盖请求头
+#[derive(Debug)]
+pub struct Redactor {
+    fields: HashSet<String>,
+    paths: Vec<Vec<String>>,
+    headers: HashSet<String>,
+    mask: String,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Self {
+        Self {
+            fields: config
+                .fields
+                .iter()
+                .map(|f| f.to_ascii_lowercase())
+                .collect(),
+            paths: config
+                .paths
+                .iter()
+                .map(|p| {
+                    p.trim_start_matches("$.")
+                        .split('.')
+                        .map(String::from)
+                        .collect()
+                })
+                .collect(),
+            headers: config
+                .headers
+                .iter()
+                .map(|h| h.to_ascii_lowercase())
+                .collect(),
+            mask: config.mask.clone(),
+        }
+    }
+
+    /// 脱敏 body：JSON 按规则遮盖后重新序列化，表单按字段名遮盖，
+    /// 其余文本（如被截断的 JSON）按字段名遮盖 `"key": value` 片段
+    pub fn redact_body(&self, bytes: &[u8]) -> String {
+        if let Ok(mut value) = serde_json::from_slice::<Value>(bytes) {
+            self.redact_value(&mut value, &mut Vec::new());
+            return value.to_string();
+        }
+
+        let text = String::from_utf8_lossy(bytes);
+        self.redact_form(&text)
+            .unwrap_or_else(|| self.redact_fragment(&text))
+    }
+
+    /// 以 JSON 对象形式输出请求头，敏感头的值被遮盖
+    pub fn redact_headers(&self, headers: &HeaderMap) -> String {
+        let map: serde_json::Map<String, Value> = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.headers.contains(name.as_str()) {
+                    self.mask.clone()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), Value::String(value))
+            })
+            .collect();
+        Value::Object(map).to_string()
+    }
+
+    fn redact_value(&self, value: &mut Value, path: &mut Vec<String>) {
+        if !path.is_empty() && self.matches_path(path) {
+            *value = Value::String(self.mask.clone());
+            return;
+        }
+
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    if self.fields.contains(&key.to_ascii_lowercase()) {
+                        *child = Value::String(self.mask.clone());
+                        continue;
+                    }
+                    path.push(key.clone());
+                    self.redact_value(child, path);
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                for (i, child) in items.iter_mut().enumerate() {
+                    path.push(i.to_string());
+                    self.redact_value(child, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn matches_path(&self, path: &[String]) -> bool {
+        self.paths.iter().any(|pattern| {
+            pattern.len() == path.len()
+                && pattern
+                    .iter()
+                    .zip(path)
+                    .all(|(p, seg)| p == "*" || p == seg)
+        })
+    }
+
+    /// 无法解析的 JSON 片段：遮盖敏感字段名之后的值，直到字符串结束或文本末尾
+    fn redact_fragment(&self, text: &str) -> String {
+        // 只做 ASCII 小写，字节下标与原文一致
+        let lower = text.to_ascii_lowercase();
+        let bytes = text.as_bytes();
+        let mut ranges = Vec::new();
+
+        for field in &self.fields {
+            let key = format!("\"{field}\"");
+            for (pos, _) in lower.match_indices(&key) {
+                let mut i = pos + key.len();
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if bytes.get(i) != Some(&b':') {
+                    continue;
+                }
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if i >= bytes.len() {
+                    continue;
+                }
+                let (start, mut end) = if bytes[i] == b'"' {
+                    (i + 1, i + 1)
+                } else {
+                    (i, i)
+                };
+                if start > i {
+                    while end < bytes.len() && bytes[end] != b'"' {
+                        end += if bytes[end] == b'\\' { 2 } else { 1 };
+                    }
+                } else {
+                    while end < bytes.len() && !b",}]".contains(&bytes[end]) {
+                        end += 1;
+                    }
+                }
+                ranges.push((start, end.min(bytes.len())));
+            }
+        }
+
+        ranges.sort_unstable();
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end) in ranges {
+            if start < last {
+                continue;
+            }
+            out.push_str(&text[last..start]);
+            out.push_str(&self.mask);
+            last = end;
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+
+    /// `a=1&password=xxx` 形式的表单 body
+    fn redact_form(&self, text: &str) -> Option<String> {
+        let text = text.trim();
+        if text.is_empty() || text.contains(char::is_whitespace) {
+            return None;
+        }
+        let pairs: Vec<(&str, &str)> = text
+            .split('&')
+            .map(|pair| pair.split_once('='))
+            .collect::<Option<_>>()?;
+        Some(
+            pairs
+                .into_iter()
+                .map(|(k, v)| {
+                    if self.fields.contains(&k.to_ascii_lowercase()) {
+                        format!("{k}={}", self.mask)
+                    } else {
+                        format!("{k}={v}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_cover_auth_payloads() {
+        let redactor = Redactor::new(&RedactionConfig::default());
+
+        let login = br#"{"altcha":"eyJjaGFsbGVuZ2UiOjF9","username":"admin","password":"secret"}"#;
+        let out: Value = serde_json::from_str(&redactor.redact_body(login)).unwrap();
+        assert_eq!(out["username"], "admin");
+        assert_eq!(out["password"], "***");
+        assert_eq!(out["altcha"], "***");
+
+        let response = br#"{"code":0,"msg":"","data":{"token":"eyJhbGciOi","token_type":"Bearer","admin":{"id":1}}}"#;
+        let out: Value = serde_json::from_str(&redactor.redact_body(response)).unwrap();
+        assert_eq!(out["data"]["token"], "***");
+        assert_eq!(out["data"]["token_type"], "Bearer");
+
+        assert_eq!(
+            redactor.redact_body(b"user=a&Password=b"),
+            "user=a&Password=***"
+        );
+        assert_eq!(redactor.redact_body(b"plain text"), "plain text");
+
+        // 截断后无法解析的 JSON
+        assert_eq!(
+            redactor.redact_body(br#"{"username":"admin","Password": "sec"#),
+            r#"{"username":"admin","Password": "***"#
+        );
+    }
+
+    #[test]
+    fn test_paths_and_headers() {
+        let redactor = Redactor::new(&RedactionConfig {
+            fields: Vec::new(),
+            paths: vec!["$.data.items.*.email".into()],
+            ..RedactionConfig::default()
+        });
+        let body = br#"{"data":{"items":[{"email":"a@b.c","name":"x"}],"email":"keep"}}"#;
+        let out: Value = serde_json::from_str(&redactor.redact_body(body)).unwrap();
+        assert_eq!(out["data"]["items"][0]["email"], "***");
+        assert_eq!(out["data"]["items"][0]["name"], "x");
+        assert_eq!(out["data"]["email"], "keep");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer abc".parse().unwrap());
+        headers.insert("accept", "*/*".parse().unwrap());
+        let out: Value = serde_json::from_str(&redactor.redact_headers(&headers)).unwrap();
+        assert_eq!(out["authorization"], "***");
+        assert_eq!(out["accept"], "*/*");
+    }
+}
@@ -0,0 +1,476 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use opentelemetry::{Context, InstrumentationScope, KeyValue};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::logs::{LogBatch, LogExporter, LogProcessor, SdkLogRecord};
+use opentelemetry_sdk::trace::{Span, SpanData, SpanExporter, SpanProcessor};
+use rand::RngExt;
+use serde::Serialize;
+
+use crate::config::{BatchConfig, RetryConfig};
+
+/// 全部导出管道的计数，供自监控指标与就绪检查读取
+static PIPELINES: Mutex<Vec<Arc<ExportStats>>> = Mutex::new(Vec::new());
+
+/// 单个导出管道（信号 + 导出器）的计数
+#[derive(Debug)]
+pub struct ExportStats {
+    signal: &'static str,
+    exporter: &'static str,
+    capacity: usize,
+    /// 已入队、尚未导出完成的条数
+    queued: AtomicUsize,
+    exported: AtomicU64,
+    dropped: AtomicU64,
+    failures: AtomicU64,
+    retries: AtomicU64,
+    healthy: AtomicBool,
+}
+
+/// 导出管道的状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineStatus {
+    pub signal: &'static str,
+    pub exporter: &'static str,
+    pub healthy: bool,
+    pub queue_size: usize,
+    pub queue_capacity: usize,
+    pub exported: u64,
+    pub dropped: u64,
+    pub failures: u64,
+    pub retries: u64,
+}
+
+/// 登记一个导出管道，`signal` 为 traces / logs，`exporter` 为 otlp / file
+pub fn register(
+    signal: &'static str,
+    exporter: &'static str,
+    batch: &BatchConfig,
+) -> Arc<ExportStats> {
+    let stats = Arc::new(ExportStats {
+        signal,
+        exporter,
+        capacity: batch.max_queue_size,
+        queued: AtomicUsize::new(0),
+        exported: AtomicU64::new(0),
+        dropped: AtomicU64::new(0),
+        failures: AtomicU64::new(0),
+        retries: AtomicU64::new(0),
+        healthy: AtomicBool::new(true),
+    });
+    pipelines().push(stats.clone());
+    stats
+}
+
+fn pipelines() -> std::sync::MutexGuard<'static, Vec<Arc<ExportStats>>> {
+    PIPELINES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 全部导出管道是否健康：最近一批导出（含重试）成功即为健康
+pub fn healthy() -> bool {
+    pipelines()
+        .iter()
+        .all(|stats| stats.healthy.load(Ordering::Relaxed))
+}
+
+/// 全部导出管道的状态
+pub fn statuses() -> Vec<PipelineStatus> {
+    pipelines().iter().map(|stats| stats.status()).collect()
+}
+
+impl ExportStats {
+    fn status(&self) -> PipelineStatus {
+        PipelineStatus {
+            signal: self.signal,
+            exporter: self.exporter,
+            healthy: self.healthy.load(Ordering::Relaxed),
+            queue_size: self.queued.load(Ordering::Relaxed),
+            queue_capacity: self.capacity,
+            exported: self.exported.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 队列未满时占一个位置，满了则计为丢弃
+    fn try_enqueue(&self) -> bool {
+        let accepted = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < self.capacity).then_some(n + 1)
+            })
+            .is_ok();
+        if !accepted {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        accepted
+    }
+
+    /// 一批导出结束（含重试），失败的整批计为丢弃
+    fn finish(&self, count: usize, result: &OTelSdkResult) {
+        let _ = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                Some(n.saturating_sub(count))
+            });
+        match result {
+            Ok(()) => {
+                self.exported.fetch_add(count as u64, Ordering::Relaxed);
+                if !self.healthy.swap(true, Ordering::Relaxed) {
+                    tracing::info!(
+                        signal = self.signal,
+                        exporter = self.exporter,
+                        "Telemetry exporter recovered"
+                    );
+                }
+            }
+            Err(e) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+                // 只在状态切换时记录，避免导出日志失败时再产生大量日志
+                if self.healthy.swap(false, Ordering::Relaxed) {
+                    tracing::warn!(
+                        signal = self.signal,
+                        exporter = self.exporter,
+                        error = %e,
+                        dropped = count,
+                        "Telemetry exporter failing, dropping batches"
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 导出自监控指标，须在 MeterProvider 设为全局之后调用
+pub fn register_metrics() {
+    let meter = opentelemetry::global::meter("axum-otel-demo");
+    let attributes = |stats: &ExportStats| {
+        [
+            KeyValue::new("otel.signal", stats.signal),
+            KeyValue::new("otel.exporter", stats.exporter),
+        ]
+    };
+
+    meter
+        .u64_observable_gauge("otel.sdk.export.queue.size")
+        .with_description("Number of items waiting in the batch processor queue.")
+        .with_unit("{item}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(
+                    stats.queued.load(Ordering::Relaxed) as u64,
+                    &attributes(stats),
+                );
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("otel.sdk.export.queue.capacity")
+        .with_description("Capacity of the batch processor queue.")
+        .with_unit("{item}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(stats.capacity as u64, &attributes(stats));
+            }
+        })
+        .build();
+    meter
+        .u64_observable_counter("otel.sdk.export.exported")
+        .with_description("Number of items exported successfully.")
+        .with_unit("{item}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(stats.exported.load(Ordering::Relaxed), &attributes(stats));
+            }
+        })
+        .build();
+    meter
+        .u64_observable_counter("otel.sdk.export.dropped")
+        .with_description(
+            "Number of items dropped because the queue was full or the export failed.",
+        )
+        .with_unit("{item}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(stats.dropped.load(Ordering::Relaxed), &attributes(stats));
+            }
+        })
+        .build();
+    meter
+        .u64_observable_counter("otel.sdk.export.failures")
+        .with_description("Number of batches that failed to export after all retries.")
+        .with_unit("{batch}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(stats.failures.load(Ordering::Relaxed), &attributes(stats));
+            }
+        })
+        .build();
+    meter
+        .u64_observable_counter("otel.sdk.export.retries")
+        .with_description("Number of export retries.")
+        .with_unit("{retry}")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                observer.observe(stats.retries.load(Ordering::Relaxed), &attributes(stats));
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("otel.sdk.export.healthy")
+        .with_description("Whether the last batch was exported successfully (1) or not (0).")
+        .with_callback(move |observer| {
+            for stats in pipelines().iter() {
+                let healthy = stats.healthy.load(Ordering::Relaxed);
+                observer.observe(u64::from(healthy), &attributes(stats));
+            }
+        })
+        .build();
+}
+
+/// 重试策略：指数退避加 ±20% 抖动，总耗时不超过 `export_timeout_ms`
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(retry: &RetryConfig, batch: &BatchConfig) -> Self {
+        Self {
+            max_attempts: retry.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(retry.initial_backoff_ms),
+            max_backoff: Duration::from_millis(retry.max_backoff_ms),
+            timeout: Duration::from_millis(batch.export_timeout_ms),
+        }
+    }
+
+    /// 第 `attempt` 次失败后的等待时间（不含抖动）
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+
+    /// 导出在批处理器的专用线程上以 `block_on` 执行，退避直接阻塞该线程，
+    /// 期间新数据在队列中累积，队列满后丢弃
+    async fn run<F, Fut>(&self, stats: &ExportStats, mut export: F) -> OTelSdkResult
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = OTelSdkResult>,
+    {
+        let deadline = Instant::now() + self.timeout;
+        let mut attempt = 1;
+        loop {
+            let result = export().await;
+            let retryable = matches!(
+                result,
+                Err(OTelSdkError::InternalFailure(_) | OTelSdkError::Timeout(_))
+            );
+            if !retryable || attempt >= self.max_attempts {
+                return result;
+            }
+            let delay = self
+                .backoff(attempt)
+                .mul_f64(rand::rng().random_range(0.8..1.2));
+            if Instant::now() + delay > deadline {
+                return result;
+            }
+            stats.retries.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+/// 为导出器加上重试与计数
+#[derive(Debug)]
+pub struct ResilientExporter<E> {
+    inner: E,
+    policy: RetryPolicy,
+    stats: Arc<ExportStats>,
+}
+
+impl<E> ResilientExporter<E> {
+    pub fn new(inner: E, policy: RetryPolicy, stats: Arc<ExportStats>) -> Self {
+        Self {
+            inner,
+            policy,
+            stats,
+        }
+    }
+}
+
+impl<E: SpanExporter> SpanExporter for ResilientExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let count = batch.len();
+        let result = self
+            .policy
+            .run(&self.stats, || self.inner.export(batch.clone()))
+            .await;
+        self.stats.finish(count, &result);
+        result
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+impl<E: LogExporter> LogExporter for ResilientExporter<E> {
+    async fn export(&self, batch: LogBatch<'_>) -> OTelSdkResult {
+        // LogBatch 只能使用一次，重试时用同一组引用重新构造
+        let records: Vec<(&SdkLogRecord, &InstrumentationScope)> = batch.iter().collect();
+        let result = self
+            .policy
+            .run(&self.stats, || self.inner.export(LogBatch::new(&records)))
+            .await;
+        self.stats.finish(records.len(), &result);
+        result
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// 在批处理器前按容量计数，队列满时直接丢弃并计数
+///
+/// SDK 的批处理器不公开队列深度与丢弃数，这里自行维护：入队时 +1，导出结束时按批扣减
+#[derive(Debug)]
+pub struct BoundedProcessor<P> {
+    inner: P,
+    stats: Arc<ExportStats>,
+}
+
+impl<P> BoundedProcessor<P> {
+    pub fn new(inner: P, stats: Arc<ExportStats>) -> Self {
+        Self { inner, stats }
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for BoundedProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if self.stats.try_enqueue() {
+            self.inner.on_end(span);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+impl<P: LogProcessor> LogProcessor for BoundedProcessor<P> {
+    fn emit(&self, record: &mut SdkLogRecord, instrumentation: &InstrumentationScope) {
+        if self.stats.try_enqueue() {
+            self.inner.emit(record, instrumentation);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_retry_until_success_and_queue_bound() {
+        let batch = BatchConfig {
+            max_queue_size: 2,
+            ..BatchConfig::default()
+        };
+        let retry = RetryConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+        };
+        let policy = RetryPolicy::new(&retry, &batch);
+        assert_eq!(policy.backoff(1), Duration::from_millis(1));
+        assert_eq!(policy.backoff(5), Duration::from_millis(2));
+
+        let stats = register("traces", "test", &batch);
+        assert!(stats.try_enqueue());
+        assert!(stats.try_enqueue());
+        assert!(!stats.try_enqueue());
+
+        let mut calls = 0;
+        let result = policy
+            .run(&stats, || {
+                calls += 1;
+                let result = if calls < 3 {
+                    Err(OTelSdkError::InternalFailure("unavailable".into()))
+                } else {
+                    Ok(())
+                };
+                std::future::ready(result)
+            })
+            .await;
+        assert!(result.is_ok());
+        stats.finish(2, &result);
+
+        let status = stats.status();
+        assert_eq!(
+            (
+                status.queue_size,
+                status.exported,
+                status.dropped,
+                status.retries
+            ),
+            (0, 2, 1, 2)
+        );
+        assert!(status.healthy);
+
+        let result = policy
+            .run(&stats, || {
+                std::future::ready(Err(OTelSdkError::InternalFailure("unavailable".into())))
+            })
+            .await;
+        stats.finish(1, &result);
+        assert!(!stats.status().healthy);
+        assert_eq!(stats.status().failures, 1);
+    }
+}
@@ -0,0 +1,263 @@
+use std::fs;
+
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::resource::{
+    EnvResourceDetector, ResourceDetector, TelemetryResourceDetector,
+};
+use opentelemetry_semantic_conventions::resource::{
+    CONTAINER_ID, DEPLOYMENT_ENVIRONMENT_NAME, HOST_ARCH, HOST_NAME, K8S_CONTAINER_NAME,
+    K8S_DEPLOYMENT_NAME, K8S_NAMESPACE_NAME, K8S_NODE_NAME, K8S_POD_NAME, K8S_POD_UID, OS_TYPE,
+    PROCESS_EXECUTABLE_NAME, PROCESS_PID, PROCESS_RUNTIME_NAME, SERVICE_INSTANCE_ID, SERVICE_NAME,
+    SERVICE_NAMESPACE, SERVICE_VERSION, TELEMETRY_SDK_LANGUAGE,
+};
+use rand::RngExt;
+
+use crate::config::{BrowserTelemetryConfig, ServiceConfig};
+
+/// Kubernetes downward API 注入的环境变量与对应的资源属性
+const K8S_ENV_VARS: [(&str, &str); 6] = [
+    ("K8S_POD_NAME", K8S_POD_NAME),
+    ("K8S_POD_UID", K8S_POD_UID),
+    ("K8S_NAMESPACE_NAME", K8S_NAMESPACE_NAME),
+    ("K8S_NODE_NAME", K8S_NODE_NAME),
+    ("K8S_CONTAINER_NAME", K8S_CONTAINER_NAME),
+    ("K8S_DEPLOYMENT_NAME", K8S_DEPLOYMENT_NAME),
+];
+
+/// 创建共享的 OTel Resource
+///
+/// 优先级从低到高：SDK 信息 → 主机 / 进程 / 容器 / Kubernetes 探测 → 配置与 `--env` →
+/// `OTEL_RESOURCE_ATTRIBUTES` → `OTEL_SERVICE_NAME`，便于运维在不改配置的情况下覆盖
+pub fn detect(service: &ServiceConfig, environment: &str) -> Resource {
+    let instance_id = service.instance_id.clone().unwrap_or_else(random_uuid);
+    let mut attributes = vec![
+        KeyValue::new(SERVICE_NAME, service.name.clone()),
+        KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
+        KeyValue::new(SERVICE_INSTANCE_ID, instance_id),
+        KeyValue::new(DEPLOYMENT_ENVIRONMENT_NAME, environment.to_string()),
+        // 旧版语义约定，部分后端仍按此字段区分环境
+        KeyValue::new("deployment.environment", environment.to_string()),
+    ];
+    if let Some(namespace) = &service.namespace {
+        attributes.push(KeyValue::new(SERVICE_NAMESPACE, namespace.clone()));
+    }
+
+    let mut builder = Resource::builder_empty()
+        .with_detectors(&[
+            Box::new(TelemetryResourceDetector),
+            Box::new(HostResourceDetector),
+            Box::new(ProcessResourceDetector),
+            Box::new(ContainerResourceDetector),
+            Box::new(K8sResourceDetector),
+        ])
+        .with_attributes(attributes)
+        .with_detector(Box::new(EnvResourceDetector::new()));
+    if let Some(name) = std::env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+    {
+        builder = builder.with_service_name(name);
+    }
+    builder.build()
+}
+
+/// 浏览器端数据的 Resource，全部由服务端设置，不信任客户端上报的资源属性
+pub fn browser(
+    config: &BrowserTelemetryConfig,
+    service: &ServiceConfig,
+    environment: &str,
+) -> Resource {
+    let mut attributes = vec![
+        KeyValue::new(SERVICE_NAME, config.service_name.clone()),
+        KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
+        KeyValue::new(DEPLOYMENT_ENVIRONMENT_NAME, environment.to_string()),
+        KeyValue::new("deployment.environment", environment.to_string()),
+        KeyValue::new(TELEMETRY_SDK_LANGUAGE, "webjs"),
+    ];
+    if let Some(namespace) = &service.namespace {
+        attributes.push(KeyValue::new(SERVICE_NAMESPACE, namespace.clone()));
+    }
+    Resource::builder_empty()
+        .with_attributes(attributes)
+        .build()
+}
+
+/// 主机名、CPU 架构与操作系统
+struct HostResourceDetector;
+
+impl ResourceDetector for HostResourceDetector {
+    fn detect(&self) -> Resource {
+        let mut attributes = vec![
+            KeyValue::new(HOST_ARCH, host_arch()),
+            KeyValue::new(OS_TYPE, os_type()),
+        ];
+        if let Some(name) = host_name() {
+            attributes.push(KeyValue::new(HOST_NAME, name));
+        }
+        Resource::builder_empty()
+            .with_attributes(attributes)
+            .build()
+    }
+}
+
+fn host_name() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// 语义约定使用的架构名与 Rust 的 target_arch 不完全一致
+fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "x86",
+        "arm" => "arm32",
+        "powerpc64" => "ppc64",
+        "s390x" => "s390x",
+        other => other,
+    }
+}
+
+fn os_type() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "darwin",
+        other => other,
+    }
+}
+
+/// 进程 ID、可执行文件名与运行时
+struct ProcessResourceDetector;
+
+impl ResourceDetector for ProcessResourceDetector {
+    fn detect(&self) -> Resource {
+        let mut attributes = vec![
+            KeyValue::new(PROCESS_PID, i64::from(std::process::id())),
+            KeyValue::new(PROCESS_RUNTIME_NAME, "rust"),
+        ];
+        if let Some(name) = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+        {
+            attributes.push(KeyValue::new(PROCESS_EXECUTABLE_NAME, name));
+        }
+        Resource::builder_empty()
+            .with_attributes(attributes)
+            .build()
+    }
+}
+
+/// 容器 ID：cgroup v1 从 `/proc/self/cgroup` 解析，cgroup v2 从 `/proc/self/mountinfo` 解析
+struct ContainerResourceDetector;
+
+impl ResourceDetector for ContainerResourceDetector {
+    fn detect(&self) -> Resource {
+        let id = fs::read_to_string("/proc/self/cgroup")
+            .ok()
+            .and_then(|content| content.lines().find_map(container_id_from_cgroup))
+            .or_else(|| {
+                fs::read_to_string("/proc/self/mountinfo")
+                    .ok()
+                    .and_then(|content| content.lines().find_map(container_id_from_mountinfo))
+            });
+        Resource::builder_empty()
+            .with_attributes(id.map(|id| KeyValue::new(CONTAINER_ID, id)))
+            .build()
+    }
+}
+
+/// cgroup 行的最后一段路径，去掉运行时前缀与 `.scope` 后缀后为 64 位十六进制即容器 ID
+fn container_id_from_cgroup(line: &str) -> Option<String> {
+    let segment = line.rsplit('/').next()?.trim();
+    let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+    let id = ["docker-", "cri-containerd-", "crio-", "libpod-"]
+        .iter()
+        .find_map(|prefix| segment.strip_prefix(prefix))
+        .unwrap_or(segment);
+    is_container_id(id).then(|| id.to_string())
+}
+
+/// cgroup v2 下 Docker 挂载的 hostname 等文件位于 `/containers/{id}/`
+fn container_id_from_mountinfo(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .filter_map(|field| field.split_once("/containers/"))
+        .filter_map(|(_, rest)| rest.split('/').next())
+        .find(|id| is_container_id(id))
+        .map(str::to_string)
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Kubernetes 元数据，需在 Deployment 中通过 downward API 注入对应环境变量
+struct K8sResourceDetector;
+
+impl ResourceDetector for K8sResourceDetector {
+    fn detect(&self) -> Resource {
+        let attributes = K8S_ENV_VARS.iter().filter_map(|(var, key)| {
+            std::env::var(var)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| KeyValue::new(*key, value))
+        });
+        Resource::builder_empty()
+            .with_attributes(attributes)
+            .build()
+    }
+}
+
+/// 未配置 `instance_id` 时每次启动生成随机 UUID v4
+fn random_uuid() -> String {
+    let mut bytes: [u8; 16] = rand::rng().random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_id_parsing() {
+        let id = "a".repeat(64);
+        assert_eq!(
+            container_id_from_cgroup(&format!("12:memory:/docker/{id}")),
+            Some(id.clone())
+        );
+        assert_eq!(
+            container_id_from_cgroup(&format!(
+                "0::/kubepods.slice/kubepods-pod1.slice/cri-containerd-{id}.scope"
+            )),
+            Some(id.clone())
+        );
+        assert_eq!(
+            container_id_from_cgroup("0::/user.slice/session-1.scope"),
+            None
+        );
+        assert_eq!(
+            container_id_from_mountinfo(&format!(
+                "635 617 254:1 /var/lib/docker/containers/{id}/hostname /etc/hostname rw"
+            )),
+            Some(id)
+        );
+
+        let uuid = random_uuid();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+    }
+}
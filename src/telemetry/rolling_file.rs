@@ -0,0 +1,211 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+
+use crate::config::{FileLogConfig, FileRotation};
+use crate::utils::time;
+
+/// 创建非阻塞的滚动文件 writer，guard 被丢弃时写完队列中剩余的日志
+pub fn init(config: &FileLogConfig) -> io::Result<(NonBlocking, WorkerGuard)> {
+    let writer = RollingFile::open(config)?;
+    Ok(tracing_appender::non_blocking(writer))
+}
+
+/// 滚动日志文件：始终写入 `{prefix}.log`，切分时重命名为
+/// `{prefix}.{日期或时间}.log`，随后按数量与保留期清理历史文件
+pub struct RollingFile {
+    directory: PathBuf,
+    prefix: String,
+    /// 文件扩展名，日志为 `log`
+    extension: String,
+    rotation: FileRotation,
+    max_bytes: u64,
+    max_files: usize,
+    max_age: Option<Duration>,
+    file: File,
+    size: u64,
+    /// 当前文件对应的日期（北京时间）
+    date: NaiveDate,
+}
+
+impl RollingFile {
+    pub fn open(config: &FileLogConfig) -> io::Result<Self> {
+        Self::open_with_extension(config, "log")
+    }
+
+    /// 以指定扩展名打开，如 OTLP 文件导出使用 `jsonl`
+    pub fn open_with_extension(config: &FileLogConfig, extension: &str) -> io::Result<Self> {
+        let directory = PathBuf::from(&config.directory);
+        fs::create_dir_all(&directory)?;
+        let file = open_append(&directory.join(format!("{}.{extension}", config.prefix)))?;
+        let metadata = file.metadata()?;
+
+        // 沿用重启前的文件时以其最后修改日期为准，跨天后首次写入即切分
+        let date = metadata
+            .modified()
+            .ok()
+            .filter(|_| metadata.len() > 0)
+            .map(beijing_date)
+            .unwrap_or_else(|| time::now().date());
+
+        let rolling = Self {
+            directory,
+            prefix: config.prefix.clone(),
+            extension: extension.to_string(),
+            rotation: config.rotation,
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            max_age: (config.max_age_days > 0)
+                .then(|| Duration::from_secs(config.max_age_days * 24 * 60 * 60)),
+            file,
+            size: metadata.len(),
+            date,
+        };
+        rolling.prune();
+        Ok(rolling)
+    }
+
+    fn active_path(&self) -> PathBuf {
+        self.directory
+            .join(format!("{}.{}", self.prefix, self.extension))
+    }
+
+    fn should_rotate(&self, incoming: usize) -> bool {
+        match self.rotation {
+            FileRotation::Daily => time::now().date() != self.date,
+            FileRotation::Size => self.size > 0 && self.size + incoming as u64 > self.max_bytes,
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let stamp = match self.rotation {
+            FileRotation::Daily => self.date.format("%Y-%m-%d").to_string(),
+            FileRotation::Size => time::now().format("%Y-%m-%d-%H%M%S").to_string(),
+        };
+        // 先更新状态，切分失败时不会每条日志都重试
+        self.date = time::now().date();
+        self.size = 0;
+
+        let mut target = self
+            .directory
+            .join(format!("{}.{stamp}.{}", self.prefix, self.extension));
+        let mut n = 1;
+        while target.exists() {
+            target = self
+                .directory
+                .join(format!("{}.{stamp}.{n}.{}", self.prefix, self.extension));
+            n += 1;
+        }
+        fs::rename(self.active_path(), &target)?;
+        self.file = open_append(&self.active_path())?;
+        self.prune();
+        Ok(())
+    }
+
+    /// 删除超出数量或保留期的历史文件，从最新的开始计数
+    fn prune(&self) {
+        let active = format!("{}.{}", self.prefix, self.extension);
+        let prefix = format!("{}.", self.prefix);
+        let suffix = format!(".{}", self.extension);
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return;
+        };
+
+        let mut history: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name != active && name.starts_with(&prefix) && name.ends_with(&suffix)
+            })
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        history.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+        let now = SystemTime::now();
+        for (i, (modified, path)) in history.iter().enumerate() {
+            let over_count = self.max_files > 0 && i >= self.max_files;
+            let expired = self.max_age.is_some_and(|max_age| {
+                now.duration_since(*modified).is_ok_and(|age| age > max_age)
+            });
+            if (over_count || expired)
+                && let Err(e) = fs::remove_file(path)
+            {
+                eprintln!("Failed to remove log file {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len())
+            && let Err(e) = self.rotate()
+        {
+            eprintln!("Failed to rotate log file: {e}");
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn beijing_date(time: SystemTime) -> NaiveDate {
+    DateTime::<Utc>::from(time)
+        .with_timezone(&chrono_tz::Asia::Shanghai)
+        .date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rotation_keeps_max_files() {
+        let directory = std::env::temp_dir().join(format!("rolling-file-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let config = FileLogConfig {
+            enabled: true,
+            directory: directory.to_string_lossy().into_owned(),
+            prefix: "app".into(),
+            rotation: FileRotation::Size,
+            max_bytes: 16,
+            max_files: 2,
+            ..FileLogConfig::default()
+        };
+
+        let mut file = RollingFile::open(&config).unwrap();
+        for i in 0..5 {
+            file.write_all(format!("line {i} .....\n").as_bytes())
+                .unwrap();
+        }
+        file.flush().unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 3, "{names:?}");
+        assert!(names.contains(&"app.log".to_string()));
+        assert_eq!(
+            fs::read_to_string(directory.join("app.log")).unwrap(),
+            "line 4 .....\n"
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
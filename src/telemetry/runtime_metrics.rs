@@ -0,0 +1,392 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Meter;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio_metrics::{TaskMetrics, TaskMonitor};
+
+use crate::config::MetricsConfig;
+
+static TASK_MONITOR: OnceLock<TaskMonitor> = OnceLock::new();
+static SNAPSHOT: RwLock<Option<Snapshot>> = RwLock::new(None);
+
+/// 经 `spawn_blocking` 提交、尚未拿到线程的任务数
+static BLOCKING_QUEUED: AtomicU64 = AtomicU64::new(0);
+/// 经 `spawn_blocking` 提交、正在执行的任务数
+static BLOCKING_RUNNING: AtomicU64 = AtomicU64::new(0);
+
+/// 请求任务监视器，由 `log_bodies` 包裹每个请求的 future，统计轮询与调度延迟
+pub fn task_monitor() -> &'static TaskMonitor {
+    TASK_MONITOR.get_or_init(TaskMonitor::new)
+}
+
+/// 在阻塞线程池上执行 `f`，并计入排队与执行中的阻塞任务数
+///
+/// tokio 的阻塞池队列深度只在 `tokio_unstable` 下可读，应用自己的阻塞任务（bcrypt、profile 导出）
+/// 都经由这里提交，以便在稳定构建中也能看到阻塞池是否排队
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let queued = Gauge::inc(&BLOCKING_QUEUED);
+    tokio::task::spawn_blocking(move || {
+        drop(queued);
+        let _running = Gauge::inc(&BLOCKING_RUNNING);
+        f()
+    })
+}
+
+/// 计数守卫：任务开始、结束、panic 或未执行就被丢弃时都会减回去
+struct Gauge(&'static AtomicU64);
+
+impl Gauge {
+    fn inc(counter: &'static AtomicU64) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for Gauge {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 一次采集的结果，可观测指标读取最近一次快照
+#[derive(Debug, Default)]
+struct Snapshot {
+    workers: u64,
+    alive_tasks: u64,
+    global_queue_depth: u64,
+    #[cfg(tokio_unstable)]
+    blocking_queue_depth: u64,
+    busy_ratio: f64,
+    mean_poll_duration: f64,
+    mean_scheduled_duration: f64,
+    slow_poll_ratio: f64,
+    process: Option<ProcessStats>,
+}
+
+#[derive(Debug, Default)]
+struct ProcessStats {
+    cpu_user: f64,
+    cpu_system: f64,
+    memory_rss: u64,
+    memory_virtual: u64,
+    open_fds: u64,
+}
+
+/// 注册运行时与进程指标，并在当前 tokio 运行时中启动周期采集任务
+pub fn start(config: &MetricsConfig) {
+    let Ok(handle) = Handle::try_current() else {
+        return;
+    };
+    register(&opentelemetry::global::meter("axum-otel-demo"));
+
+    let interval = Duration::from_secs(config.runtime_interval_secs.max(1));
+    handle.spawn(async move {
+        let mut sampler = Sampler::new(Handle::current());
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let snapshot = sampler.sample();
+            *SNAPSHOT.write().unwrap_or_else(|e| e.into_inner()) = Some(snapshot);
+        }
+    });
+}
+
+/// 采集器：保存上一次的累计值，按间隔计算繁忙度与平均延迟
+struct Sampler {
+    handle: Handle,
+    last_at: Instant,
+    last_busy: Duration,
+    tasks: Box<dyn Iterator<Item = TaskMetrics> + Send>,
+}
+
+impl Sampler {
+    fn new(handle: Handle) -> Self {
+        let last_busy = total_busy(&handle);
+        Self {
+            handle,
+            last_at: Instant::now(),
+            last_busy,
+            tasks: Box::new(task_monitor().intervals()),
+        }
+    }
+
+    fn sample(&mut self) -> Snapshot {
+        let metrics = self.handle.metrics();
+        let workers = metrics.num_workers();
+
+        // 区间内所有 worker 的繁忙时间之和 / (区间长度 × worker 数)
+        let now = Instant::now();
+        let busy = total_busy(&self.handle);
+        let elapsed = now.duration_since(self.last_at).as_secs_f64() * workers as f64;
+        let busy_ratio = if elapsed > 0.0 {
+            (busy.saturating_sub(self.last_busy).as_secs_f64() / elapsed).min(1.0)
+        } else {
+            0.0
+        };
+        self.last_at = now;
+        self.last_busy = busy;
+
+        let tasks = self.tasks.next().unwrap_or_default();
+
+        Snapshot {
+            workers: workers as u64,
+            alive_tasks: metrics.num_alive_tasks() as u64,
+            global_queue_depth: metrics.global_queue_depth() as u64,
+            #[cfg(tokio_unstable)]
+            blocking_queue_depth: metrics.blocking_queue_depth() as u64,
+            busy_ratio,
+            mean_poll_duration: tasks.mean_poll_duration().as_secs_f64(),
+            mean_scheduled_duration: tasks.mean_scheduled_duration().as_secs_f64(),
+            slow_poll_ratio: nan_to_zero(tasks.slow_poll_ratio()),
+            process: process_stats(),
+        }
+    }
+}
+
+fn total_busy(handle: &Handle) -> Duration {
+    let metrics = handle.metrics();
+    (0..metrics.num_workers())
+        .map(|worker| metrics.worker_total_busy_duration(worker))
+        .sum()
+}
+
+/// 区间内没有轮询时比例为 NaN，按 0 上报
+fn nan_to_zero(value: f64) -> f64 {
+    if value.is_nan() { 0.0 } else { value }
+}
+
+#[cfg(target_os = "linux")]
+fn process_stats() -> Option<ProcessStats> {
+    let process = procfs::process::Process::myself().ok()?;
+    let stat = process.stat().ok()?;
+    let ticks = procfs::ticks_per_second() as f64;
+    Some(ProcessStats {
+        cpu_user: stat.utime as f64 / ticks,
+        cpu_system: stat.stime as f64 / ticks,
+        memory_rss: stat.rss * procfs::page_size(),
+        memory_virtual: stat.vsize,
+        open_fds: process.fd_count().ok()? as u64,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_stats() -> Option<ProcessStats> {
+    None
+}
+
+/// 从最近一次快照中读取数值
+fn read<T>(f: impl FnOnce(&Snapshot) -> Option<T>) -> Option<T> {
+    SNAPSHOT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(f)
+}
+
+fn register(meter: &Meter) {
+    meter
+        .u64_observable_gauge("tokio.runtime.workers")
+        .with_description("Number of tokio worker threads.")
+        .with_unit("{thread}")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.workers)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("tokio.runtime.alive_tasks")
+        .with_description("Number of alive tasks in the tokio runtime.")
+        .with_unit("{task}")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.alive_tasks)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("tokio.runtime.global_queue.depth")
+        .with_description("Number of tasks waiting in the runtime's global queue.")
+        .with_unit("{task}")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.global_queue_depth)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("app.blocking.tasks.queued")
+        .with_description("Number of application blocking tasks waiting for a blocking-pool thread.")
+        .with_unit("{task}")
+        .with_callback(|observer| {
+            observer.observe(BLOCKING_QUEUED.load(Ordering::Relaxed), &[]);
+        })
+        .build();
+    meter
+        .u64_observable_gauge("app.blocking.tasks.running")
+        .with_description("Number of application blocking tasks running on the blocking pool.")
+        .with_unit("{task}")
+        .with_callback(|observer| {
+            observer.observe(BLOCKING_RUNNING.load(Ordering::Relaxed), &[]);
+        })
+        .build();
+    // 阻塞线程池队列深度依赖 tokio 不稳定 API，需以 `RUSTFLAGS="--cfg tokio_unstable"` 构建才会导出
+    #[cfg(tokio_unstable)]
+    meter
+        .u64_observable_gauge("tokio.runtime.blocking_queue.depth")
+        .with_description("Number of tasks waiting for a thread in the blocking pool.")
+        .with_unit("{task}")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.blocking_queue_depth)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .f64_observable_gauge("tokio.runtime.worker.busy_ratio")
+        .with_description("Share of time worker threads spent polling tasks.")
+        .with_unit("1")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.busy_ratio)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .f64_observable_gauge("tokio.task.poll.duration")
+        .with_description("Mean duration of a single poll of request tasks.")
+        .with_unit("s")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.mean_poll_duration)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .f64_observable_gauge("tokio.task.scheduled.duration")
+        .with_description("Mean time request tasks waited to be polled after being woken.")
+        .with_unit("s")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.mean_scheduled_duration)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .f64_observable_gauge("tokio.task.slow_poll.ratio")
+        .with_description("Share of request task polls that took longer than 50ms.")
+        .with_unit("1")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| Some(s.slow_poll_ratio)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+
+    // 进程指标（OTel process 语义约定）
+    meter
+        .f64_observable_counter("process.cpu.time")
+        .with_description("Total CPU seconds broken down by different CPU modes.")
+        .with_unit("s")
+        .with_callback(|observer| {
+            if let Some((user, system)) =
+                read(|s| s.process.as_ref().map(|p| (p.cpu_user, p.cpu_system)))
+            {
+                observer.observe(user, &[KeyValue::new("cpu.mode", "user")]);
+                observer.observe(system, &[KeyValue::new("cpu.mode", "system")]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("process.memory.usage")
+        .with_description("The amount of physical memory in use.")
+        .with_unit("By")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| s.process.as_ref().map(|p| p.memory_rss)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("process.memory.virtual")
+        .with_description("The amount of committed virtual memory.")
+        .with_unit("By")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| s.process.as_ref().map(|p| p.memory_virtual)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("process.open_file_descriptor.count")
+        .with_description("Number of file descriptors in use by the process.")
+        .with_unit("{file_descriptor}")
+        .with_callback(|observer| {
+            if let Some(value) = read(|s| s.process.as_ref().map(|p| p.open_fds)) {
+                observer.observe(value, &[]);
+            }
+        })
+        .build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+    use crate::telemetry::prometheus::PrometheusExporter;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_sample_exports_gauges() {
+        let exporter = PrometheusExporter::new();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(exporter.reader())
+            .build();
+        register(&provider.meter("runtime-test"));
+
+        // 阻塞任务执行期间计入 running，结束后归零
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let task = spawn_blocking(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        });
+        started_rx.recv().unwrap();
+
+        let mut sampler = Sampler::new(Handle::current());
+        *SNAPSHOT.write().unwrap() = Some(sampler.sample());
+        let text = exporter.render();
+
+        for name in [
+            "tokio_runtime_workers{",
+            "tokio_runtime_alive_tasks{",
+            "tokio_runtime_global_queue_depth{",
+            "tokio_runtime_worker_busy_ratio{",
+            "tokio_task_poll_duration_seconds{",
+            "tokio_task_scheduled_duration_seconds{",
+            "tokio_task_slow_poll_ratio{",
+            "app_blocking_tasks_queued{",
+        ] {
+            assert!(text.contains(name), "missing {name} in\n{text}");
+        }
+        assert!(text.contains("tokio_runtime_workers{otel_scope_name=\"runtime-test\"} 2"));
+        assert!(text.contains("app_blocking_tasks_running{otel_scope_name=\"runtime-test\"} 1"));
+        #[cfg(target_os = "linux")]
+        assert!(text.contains("process_memory_usage_bytes{"));
+
+        release_tx.send(()).unwrap();
+        task.await.unwrap();
+        assert_eq!(BLOCKING_RUNNING.load(Ordering::Relaxed), 0);
+        assert_eq!(BLOCKING_QUEUED.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,612 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanContext, SpanId, SpanKind, Status,
+    TraceContextExt, TraceFlags, TraceId,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Sampler, ShouldSample, Span, SpanData, SpanProcessor};
+use opentelemetry_sdk::Resource;
+
+use crate::config::{SamplingConfig, SamplingRule, SamplingStrategy};
+
+/// 等待根 span 结束期间最多暂存的未采样 span 数，超出时淘汰最早的 trace
+const MAX_PENDING_SPANS: usize = 10_000;
+
+/// 最多记住多少条已决策 trace 的去留
+const MAX_DECIDED_TRACES: usize = 10_000;
+
+/// 暂存的 trace 与决策记录的保留时长
+const TRACE_TTL: Duration = Duration::from_secs(60);
+
+/// 当前生效的采样配置，采样器每次决策时读取，热更新后立即生效
+static SAMPLING_CONFIG: RwLock<Option<Arc<SamplingConfig>>> = RwLock::new(None);
+
+/// 获取当前采样配置
+pub fn current() -> Arc<SamplingConfig> {
+    SAMPLING_CONFIG
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// 替换采样配置，无需重启
+pub fn reload(config: SamplingConfig) {
+    *SAMPLING_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(config));
+}
+
+/// 校验采样比例是否在 [0, 1] 区间
+pub fn validate(config: &SamplingConfig) -> Result<(), String> {
+    let ratios = std::iter::once(("ratio", config.ratio))
+        .chain(config.rules.iter().map(|r| (r.route.as_str(), r.ratio)));
+    for (name, ratio) in ratios {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(format!("{name} 的采样比例必须在 0 到 1 之间"));
+        }
+    }
+    Ok(())
+}
+
+/// 路由模板匹配，以 `*` 结尾表示前缀匹配
+pub(crate) fn route_matches(pattern: &str, route: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => route.starts_with(prefix),
+        None => route == pattern,
+    }
+}
+
+impl SamplingConfig {
+    fn rule_for(&self, route: Option<&str>) -> Option<&SamplingRule> {
+        let route = route?;
+        self.rules.iter().find(|rule| route_matches(&rule.route, route))
+    }
+
+    /// 是否启用了基于结果的保留规则（出错 / 慢请求）
+    fn tail_rules_enabled(&self) -> bool {
+        self.keep_errors
+            || self.slow_threshold_ms.is_some()
+            || self.rules.iter().any(|r| r.slow_threshold_ms.is_some())
+    }
+
+    fn slow_threshold(&self, route: Option<&str>) -> Option<Duration> {
+        self.rule_for(route)
+            .and_then(|r| r.slow_threshold_ms)
+            .or(self.slow_threshold_ms)
+            .map(Duration::from_millis)
+    }
+}
+
+/// 基于配置的头部采样器
+///
+/// 未命中采样但启用了出错/慢请求保留时返回 RecordOnly，由 `TailSamplingProcessor` 在根 span 结束后决定去留
+#[derive(Clone, Debug)]
+pub struct RuleBasedSampler;
+
+impl ShouldSample for RuleBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        _name: &str,
+        _span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        _links: &[Link],
+    ) -> SamplingResult {
+        let config = current();
+        let parent = parent_context
+            .map(|cx| cx.span().span_context().clone())
+            .filter(SpanContext::is_valid);
+        let trace_state = parent
+            .as_ref()
+            .map(|sc| sc.trace_state().clone())
+            .unwrap_or_default();
+
+        SamplingResult {
+            decision: decide(&config, parent.as_ref(), trace_id, attributes),
+            attributes: Vec::new(),
+            trace_state,
+        }
+    }
+}
+
+/// 按采样配置对新 span 做头部决策
+fn decide(
+    config: &SamplingConfig,
+    parent: Option<&SpanContext>,
+    trace_id: TraceId,
+    attributes: &[KeyValue],
+) -> SamplingDecision {
+    let sampled = match parent {
+        // 进程内子 span 跟随本地根 span 的决策
+        Some(sc) if !sc.is_remote() => sc.is_sampled(),
+        // 上游已决定不采样：直接丢弃，不为尾部保留暂存（上游没有这条 trace 的其余部分）
+        Some(sc) if config.strategy == SamplingStrategy::ParentBased && !sc.is_sampled() => {
+            return SamplingDecision::Drop;
+        }
+        _ => match config.strategy {
+            SamplingStrategy::AlwaysOn => true,
+            SamplingStrategy::AlwaysOff => false,
+            SamplingStrategy::ParentBased if parent.is_some() => true,
+            SamplingStrategy::Ratio | SamplingStrategy::ParentBased => {
+                let ratio = config
+                    .rule_for(route_attribute(attributes))
+                    .map_or(config.ratio, |r| r.ratio);
+                Sampler::TraceIdRatioBased(ratio)
+                    .should_sample(None, trace_id, "", &SpanKind::Server, attributes, &[])
+                    .decision
+                    == SamplingDecision::RecordAndSample
+            }
+        },
+    };
+
+    if sampled {
+        SamplingDecision::RecordAndSample
+    } else if config.tail_rules_enabled() {
+        SamplingDecision::RecordOnly
+    } else {
+        SamplingDecision::Drop
+    }
+}
+
+fn route_attribute(attributes: &[KeyValue]) -> Option<&str> {
+    attributes.iter().find(|kv| kv.key.as_str() == "http.route").and_then(|kv| match &kv.value {
+        opentelemetry::Value::String(s) => Some(s.as_str()),
+        _ => None,
+    })
+}
+
+/// 尾部保留处理器：已采样的 span 直接交给各导出处理器；RecordOnly 的 span 按 trace 暂存，
+/// 等本地根 span（server span）结束时，若整条 trace 出错或根 span 超过慢请求阈值则补发，否则丢弃
+///
+/// 多个导出处理器共用同一份暂存与决策，未采样的 span 只缓冲一次
+#[derive(Debug)]
+pub struct TailSamplingProcessor {
+    inner: Vec<Box<dyn SpanProcessor>>,
+    state: Mutex<TailState>,
+}
+
+#[derive(Debug, Default)]
+struct TailState {
+    /// 等待本地根 span 结束的 trace
+    pending: FifoMap<Vec<SpanData>>,
+    /// `pending` 中的 span 总数
+    len: usize,
+    /// 根 span 已结束的 trace 是否保留，晚于根 span 结束的子 span（如后台任务）跟随该决策
+    decided: FifoMap<bool>,
+}
+
+impl TailState {
+    /// 淘汰超过 TTL 的暂存 trace 与决策记录
+    fn expire(&mut self, now: Instant) {
+        let Some(deadline) = now.checked_sub(TRACE_TTL) else {
+            return;
+        };
+        for spans in self.pending.expire(deadline) {
+            self.len -= spans.len();
+        }
+        self.decided.expire(deadline);
+    }
+}
+
+/// 按 trace 首次出现的顺序淘汰的表
+#[derive(Debug)]
+struct FifoMap<V> {
+    entries: HashMap<TraceId, (u64, Instant, V)>,
+    order: BTreeMap<u64, TraceId>,
+    seq: u64,
+}
+
+impl<V> Default for FifoMap<V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            seq: 0,
+        }
+    }
+}
+
+impl<V> FifoMap<V> {
+    fn get(&self, trace_id: &TraceId) -> Option<&V> {
+        self.entries.get(trace_id).map(|(_, _, v)| v)
+    }
+
+    fn get_mut(&mut self, trace_id: &TraceId) -> Option<&mut V> {
+        self.entries.get_mut(trace_id).map(|(_, _, v)| v)
+    }
+
+    fn insert(&mut self, trace_id: TraceId, now: Instant, value: V) {
+        self.remove(&trace_id);
+        self.seq += 1;
+        self.order.insert(self.seq, trace_id);
+        self.entries.insert(trace_id, (self.seq, now, value));
+    }
+
+    fn remove(&mut self, trace_id: &TraceId) -> Option<V> {
+        let (seq, _, value) = self.entries.remove(trace_id)?;
+        self.order.remove(&seq);
+        Some(value)
+    }
+
+    fn pop_oldest(&mut self) -> Option<V> {
+        let (_, trace_id) = self.order.pop_first()?;
+        self.entries.remove(&trace_id).map(|(_, _, v)| v)
+    }
+
+    /// 移除早于 `deadline` 插入的条目
+    fn expire(&mut self, deadline: Instant) -> Vec<V> {
+        let mut expired = Vec::new();
+        while let Some((_, trace_id)) = self.order.first_key_value() {
+            if self.entries.get(trace_id).is_some_and(|(_, at, _)| *at >= deadline) {
+                break;
+            }
+            expired.extend(self.pop_oldest());
+        }
+        expired
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl TailSamplingProcessor {
+    pub fn new(inner: Vec<Box<dyn SpanProcessor>>) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(TailState::default()),
+        }
+    }
+
+    fn should_keep(config: &SamplingConfig, root: &SpanData, spans: &[SpanData]) -> bool {
+        let has_error = std::iter::once(root)
+            .chain(spans)
+            .any(|s| matches!(s.status, Status::Error { .. }));
+        if config.keep_errors && has_error {
+            return true;
+        }
+
+        let route = root
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == "http.route")
+            .map(|kv| kv.value.as_str());
+        let elapsed = root.end_time.duration_since(root.start_time).unwrap_or_default();
+        config
+            .slow_threshold(route.as_deref())
+            .is_some_and(|threshold| elapsed >= threshold)
+    }
+
+    /// 交给每个导出处理器
+    fn export(&self, span: SpanData) {
+        if let Some((last, rest)) = self.inner.split_last() {
+            for processor in rest {
+                processor.on_end(span.clone());
+            }
+            last.on_end(span);
+        }
+    }
+}
+
+/// 把未采样的 span 标记为已采样后再交给导出器
+fn mark_sampled(mut span: SpanData) -> SpanData {
+    let sc = &span.span_context;
+    span.span_context = SpanContext::new(
+        sc.trace_id(),
+        sc.span_id(),
+        sc.trace_flags() | TraceFlags::SAMPLED,
+        sc.is_remote(),
+        sc.trace_state().clone(),
+    );
+    span
+}
+
+impl SpanProcessor for TailSamplingProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        for processor in &self.inner {
+            processor.on_start(span, cx);
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if span.span_context.is_sampled() {
+            self.export(span);
+            return;
+        }
+
+        let now = Instant::now();
+        let trace_id = span.span_context.trace_id();
+        let is_local_root = span.span_kind == SpanKind::Server || span.parent_span_id == SpanId::INVALID;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.expire(now);
+
+        // 根 span 已决策，子 span 直接跟随
+        if let Some(&keep) = state.decided.get(&trace_id) {
+            drop(state);
+            if keep {
+                self.export(mark_sampled(span));
+            }
+            return;
+        }
+
+        if !is_local_root {
+            match state.pending.get_mut(&trace_id) {
+                Some(spans) => spans.push(span),
+                None => state.pending.insert(trace_id, now, vec![span]),
+            }
+            state.len += 1;
+            // 缓冲区满时淘汰最早的 trace，进行中的其他 trace 不受影响
+            while state.len > MAX_PENDING_SPANS {
+                let Some(spans) = state.pending.pop_oldest() else {
+                    break;
+                };
+                state.len -= spans.len();
+            }
+            return;
+        }
+
+        let spans = state.pending.remove(&trace_id).unwrap_or_default();
+        state.len -= spans.len();
+        let keep = Self::should_keep(&current(), &span, &spans);
+        state.decided.insert(trace_id, now, keep);
+        if state.decided.len() > MAX_DECIDED_TRACES {
+            state.decided.pop_oldest();
+        }
+        drop(state);
+
+        if keep {
+            for child in spans {
+                self.export(mark_sampled(child));
+            }
+            self.export(mark_sampled(span));
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner
+            .iter()
+            .map(|processor| processor.force_flush())
+            .fold(Ok(()), Result::and)
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner
+            .iter()
+            .map(|processor| processor.shutdown())
+            .fold(Ok(()), Result::and)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        for processor in &mut self.inner {
+            processor.set_resource(resource);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::time::SystemTime;
+
+    use opentelemetry::InstrumentationScope;
+    use opentelemetry::trace::TraceState;
+    use opentelemetry_sdk::trace::{SpanEvents, SpanLinks};
+
+    #[derive(Debug, Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<SpanData>>>);
+
+    impl Collect {
+        fn len(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+    }
+
+    impl SpanProcessor for Collect {
+        fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.0.lock().unwrap().push(span);
+        }
+
+        fn force_flush(&self) -> OTelSdkResult {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> OTelSdkResult {
+            Ok(())
+        }
+    }
+
+    fn span(trace: u128, id: u64, parent: u64, elapsed_ms: u64, error: bool) -> SpanData {
+        let start_time = SystemTime::UNIX_EPOCH;
+        SpanData {
+            span_context: SpanContext::new(
+                TraceId::from(trace),
+                SpanId::from(id),
+                TraceFlags::default(),
+                false,
+                TraceState::default(),
+            ),
+            parent_span_id: SpanId::from(parent),
+            span_kind: if parent == 0 {
+                SpanKind::Server
+            } else {
+                SpanKind::Internal
+            },
+            name: Cow::Borrowed("span"),
+            start_time,
+            end_time: start_time + Duration::from_millis(elapsed_ms),
+            attributes: vec![KeyValue::new("http.route", "/api/items")],
+            dropped_attributes_count: 0,
+            events: SpanEvents::default(),
+            links: SpanLinks::default(),
+            status: if error {
+                Status::error("boom")
+            } else {
+                Status::Unset
+            },
+            instrumentation_scope: InstrumentationScope::default(),
+        }
+    }
+
+    #[test]
+    fn test_keep_errors() {
+        let root = span(1, 1, 0, 5, false);
+        let children = [span(1, 2, 1, 1, true)];
+        let mut config = SamplingConfig::default();
+        assert!(TailSamplingProcessor::should_keep(&config, &root, &children));
+        assert!(!TailSamplingProcessor::should_keep(&config, &root, &[]));
+
+        config.keep_errors = false;
+        assert!(!TailSamplingProcessor::should_keep(&config, &root, &children));
+    }
+
+    #[test]
+    fn test_keep_slow_requests() {
+        let mut config = SamplingConfig {
+            slow_threshold_ms: Some(100),
+            ..SamplingConfig::default()
+        };
+        assert!(TailSamplingProcessor::should_keep(&config, &span(1, 1, 0, 100, false), &[]));
+        assert!(!TailSamplingProcessor::should_keep(&config, &span(1, 1, 0, 99, false), &[]));
+
+        // 路由规则的阈值优先于全局阈值
+        config.rules.push(SamplingRule {
+            route: "/api/*".into(),
+            ratio: 0.0,
+            slow_threshold_ms: Some(1000),
+        });
+        assert!(!TailSamplingProcessor::should_keep(&config, &span(1, 1, 0, 500, false), &[]));
+    }
+
+    #[test]
+    fn test_ratio_decision() {
+        let route = [KeyValue::new("http.route", "/api/items")];
+        let trace_id = TraceId::from(u128::MAX / 3);
+        let mut config = SamplingConfig {
+            strategy: SamplingStrategy::Ratio,
+            ratio: 1.0,
+            ..SamplingConfig::default()
+        };
+        assert_eq!(decide(&config, None, trace_id, &route), SamplingDecision::RecordAndSample);
+
+        // 未采样时若启用了出错保留则先记录，等根 span 结束再决定
+        config.ratio = 0.0;
+        assert_eq!(decide(&config, None, trace_id, &route), SamplingDecision::RecordOnly);
+        config.keep_errors = false;
+        assert_eq!(decide(&config, None, trace_id, &route), SamplingDecision::Drop);
+
+        config.rules.push(SamplingRule {
+            route: "/api/*".into(),
+            ratio: 1.0,
+            slow_threshold_ms: None,
+        });
+        assert_eq!(decide(&config, None, trace_id, &route), SamplingDecision::RecordAndSample);
+        assert_eq!(decide(&config, None, trace_id, &[]), SamplingDecision::Drop);
+    }
+
+    #[test]
+    fn test_remote_parent_decision() {
+        let remote = |flags| {
+            SpanContext::new(
+                TraceId::from(1),
+                SpanId::from(1),
+                flags,
+                true,
+                TraceState::default(),
+            )
+        };
+        let (sampled, unsampled) = (remote(TraceFlags::SAMPLED), remote(TraceFlags::default()));
+        let trace_id = TraceId::from(1);
+        let mut config = SamplingConfig {
+            strategy: SamplingStrategy::ParentBased,
+            ratio: 0.0,
+            ..SamplingConfig::default()
+        };
+        assert!(config.keep_errors);
+        assert_eq!(
+            decide(&config, Some(&sampled), trace_id, &[]),
+            SamplingDecision::RecordAndSample
+        );
+        // 上游未采样时即使启用了出错保留也不暂存
+        assert_eq!(decide(&config, Some(&unsampled), trace_id, &[]), SamplingDecision::Drop);
+
+        // ratio 策略不看上游决策
+        config.strategy = SamplingStrategy::Ratio;
+        config.ratio = 1.0;
+        assert_eq!(
+            decide(&config, Some(&unsampled), trace_id, &[]),
+            SamplingDecision::RecordAndSample
+        );
+    }
+
+    #[test]
+    fn test_shared_decision_fans_out() {
+        let (otlp, file) = (Collect::default(), Collect::default());
+        let processor =
+            TailSamplingProcessor::new(vec![Box::new(otlp.clone()), Box::new(file.clone())]);
+
+        processor.on_end(span(1, 2, 1, 1, true));
+        processor.on_end(span(1, 1, 0, 5, false));
+        assert_eq!((otlp.len(), file.len()), (2, 2));
+        assert!(otlp.0.lock().unwrap().iter().all(|s| s.span_context.is_sampled()));
+
+        processor.on_end(span(2, 2, 1, 1, false));
+        processor.on_end(span(2, 1, 0, 5, false));
+        assert_eq!((otlp.len(), file.len()), (2, 2));
+        assert_eq!(processor.state.lock().unwrap().len, 0);
+    }
+
+    #[test]
+    fn test_late_children_follow_root_decision() {
+        let exported = Collect::default();
+        let processor = TailSamplingProcessor::new(vec![Box::new(exported.clone())]);
+
+        processor.on_end(span(1, 1, 0, 5, true));
+        processor.on_end(span(1, 2, 1, 1, false));
+        assert_eq!(exported.len(), 2);
+
+        processor.on_end(span(2, 1, 0, 5, false));
+        processor.on_end(span(2, 2, 1, 1, false));
+        assert_eq!(exported.len(), 2);
+        assert_eq!(processor.state.lock().unwrap().len, 0);
+    }
+
+    #[test]
+    fn test_full_buffer_evicts_oldest_trace() {
+        let exported = Collect::default();
+        let processor = TailSamplingProcessor::new(vec![Box::new(exported.clone())]);
+
+        processor.on_end(span(1, 2, 1, 1, false));
+        for id in 0..MAX_PENDING_SPANS as u64 {
+            processor.on_end(span(2, id + 2, 1, 1, false));
+        }
+        assert_eq!(processor.state.lock().unwrap().len, MAX_PENDING_SPANS);
+
+        // trace 1 被淘汰，trace 2 的子 span 完整保留
+        processor.on_end(span(1, 1, 0, 5, true));
+        assert_eq!(exported.len(), 1);
+        processor.on_end(span(2, 1, 0, 5, true));
+        assert_eq!(exported.len(), 2 + MAX_PENDING_SPANS);
+    }
+
+    #[test]
+    fn test_fifo_map_expires_oldest_first() {
+        let mut map = FifoMap::default();
+        let now = Instant::now();
+        map.insert(TraceId::from(1), now, 1);
+        map.insert(TraceId::from(2), now + Duration::from_secs(1), 2);
+        map.insert(TraceId::from(3), now + Duration::from_secs(2), 3);
+        map.remove(&TraceId::from(2));
+
+        assert_eq!(map.expire(now + Duration::from_secs(2)), vec![1]);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&TraceId::from(3)), Some(&3));
+    }
+}
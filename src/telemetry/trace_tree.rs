@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use opentelemetry::trace::TraceId;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{KeyValue, any_value};
+use opentelemetry_proto::tonic::trace::v1::Span;
+use opentelemetry_proto::tonic::trace::v1::status::StatusCode;
+
+use super::file_export;
+use crate::config::FileExportConfig;
+
+/// 每个 span 行附带显示的属性
+const SHOWN_ATTRIBUTES: [&str; 3] = [
+    "http.response.status_code",
+    "error.type",
+    "db.operation.name",
+];
+
+/// 从 OTLP-JSON 导出文件中找出一条 trace 并打印 span 树
+///
+/// 未指定文件时读取 `file_export.directory` 下全部 `{prefix}-traces*.jsonl`，含已切分的历史文件
+pub fn print(trace_id: &str, files: &[PathBuf], config: &FileExportConfig) -> Result<(), String> {
+    let trace_id =
+        TraceId::from_hex(trace_id.trim()).map_err(|_| format!("无效的 trace_id: {trace_id}"))?;
+    let files = if files.is_empty() {
+        trace_files(config)?
+    } else {
+        files.to_vec()
+    };
+
+    let mut spans = Vec::new();
+    for path in &files {
+        spans.extend(read_spans(path, trace_id)?);
+    }
+    if spans.is_empty() {
+        return Err(format!(
+            "在 {} 个文件中未找到 trace {trace_id}",
+            files.len()
+        ));
+    }
+    print!("{}", render(trace_id, spans));
+    Ok(())
+}
+
+fn trace_files(config: &FileExportConfig) -> Result<Vec<PathBuf>, String> {
+    let prefix = format!("{}-traces.", config.prefix);
+    let suffix = format!(".{}", file_export::EXTENSION);
+    let entries = fs::read_dir(&config.directory)
+        .map_err(|e| format!("无法读取目录 {}: {e}", config.directory))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(&suffix)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("目录 {} 下没有 traces 导出文件", config.directory));
+    }
+    Ok(files)
+}
+
+/// 逐行解析，写到一半的末行等无法解析的行跳过并提示
+fn read_spans(path: &Path, trace_id: TraceId) -> Result<Vec<Span>, String> {
+    let file = File::open(path).map_err(|e| format!("无法打开 {}: {e}", path.display()))?;
+    let trace_id = trace_id.to_bytes();
+    let mut spans = Vec::new();
+
+    for (n, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("读取 {} 失败: {e}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: ExportTraceServiceRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                eprintln!("跳过 {}:{}: {e}", path.display(), n + 1);
+                continue;
+            }
+        };
+        spans.extend(
+            request
+                .resource_spans
+                .into_iter()
+                .flat_map(|r| r.scope_spans)
+                .flat_map(|s| s.spans)
+                .filter(|span| span.trace_id == trace_id),
+        );
+    }
+    Ok(spans)
+}
+
+/// 按父子关系渲染为树，父 span 不在文件中（如来自上游服务）的作为根节点
+fn render(trace_id: TraceId, mut spans: Vec<Span>) -> String {
+    spans.sort_by_key(|s| s.start_time_unix_nano);
+    let start = spans.first().map_or(0, |s| s.start_time_unix_nano);
+    let end = spans
+        .iter()
+        .map(|s| s.end_time_unix_nano)
+        .max()
+        .unwrap_or(start);
+
+    let mut children: HashMap<&[u8], Vec<&Span>> = HashMap::new();
+    let mut roots = Vec::new();
+    for span in &spans {
+        let has_parent = !span.parent_span_id.is_empty()
+            && spans.iter().any(|s| s.span_id == span.parent_span_id);
+        if has_parent {
+            children
+                .entry(span.parent_span_id.as_slice())
+                .or_default()
+                .push(span);
+        } else {
+            roots.push(span);
+        }
+    }
+
+    let mut out = format!(
+        "trace {trace_id} · {} spans · {}\n",
+        spans.len(),
+        millis(end.saturating_sub(start))
+    );
+    let count = roots.len();
+    for (i, root) in roots.into_iter().enumerate() {
+        write_span(&mut out, root, &children, start, "", i + 1 == count);
+    }
+    out
+}
+
+fn write_span(
+    out: &mut String,
+    span: &Span,
+    children: &HashMap<&[u8], Vec<&Span>>,
+    trace_start: u64,
+    indent: &str,
+    last: bool,
+) {
+    let branch = if last { "└── " } else { "├── " };
+    let _ = write!(
+        out,
+        "{indent}{branch}{} [{}] {} (+{})",
+        span.name,
+        kind(span.kind),
+        millis(
+            span.end_time_unix_nano
+                .saturating_sub(span.start_time_unix_nano)
+        ),
+        millis(span.start_time_unix_nano.saturating_sub(trace_start)),
+    );
+    for kv in span
+        .attributes
+        .iter()
+        .filter(|kv| SHOWN_ATTRIBUTES.contains(&kv.key.as_str()))
+    {
+        let _ = write!(out, " {}={}", kv.key, value(kv));
+    }
+    if let Some(status) = &span.status
+        && status.code == StatusCode::Error as i32
+    {
+        let _ = write!(out, " ERROR");
+        if !status.message.is_empty() {
+            let _ = write!(out, ": {}", status.message);
+        }
+    }
+    out.push('\n');
+
+    let indent = format!("{indent}{}", if last { "    " } else { "│   " });
+    if let Some(spans) = children.get(span.span_id.as_slice()) {
+        for (i, child) in spans.iter().enumerate() {
+            write_span(
+                out,
+                child,
+                children,
+                trace_start,
+                &indent,
+                i + 1 == spans.len(),
+            );
+        }
+    }
+}
+
+fn kind(kind: i32) -> &'static str {
+    match kind {
+        1 => "internal",
+        2 => "server",
+        3 => "client",
+        4 => "producer",
+        5 => "consumer",
+        _ => "unspecified",
+    }
+}
+
+fn value(kv: &KeyValue) -> String {
+    match kv.value.as_ref().and_then(|v| v.value.as_ref()) {
+        Some(any_value::Value::StringValue(s)) => s.clone(),
+        Some(any_value::Value::IntValue(i)) => i.to_string(),
+        Some(any_value::Value::DoubleValue(f)) => f.to_string(),
+        Some(any_value::Value::BoolValue(b)) => b.to_string(),
+        Some(other) => format!("{other:?}"),
+        None => String::new(),
+    }
+}
+
+fn millis(nanos: u64) -> String {
+    format!("{:.3} ms", nanos as f64 / 1_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::common::v1::AnyValue;
+    use opentelemetry_proto::tonic::trace::v1::Status;
+
+    fn span(id: u8, parent: Option<u8>, name: &str, start: u64, end: u64) -> Span {
+        Span {
+            trace_id: vec![1; 16],
+            span_id: vec![id; 8],
+            parent_span_id: parent.map(|p| vec![p; 8]).unwrap_or_default(),
+            name: name.to_string(),
+            kind: if parent.is_none() { 2 } else { 1 },
+            start_time_unix_nano: start,
+            end_time_unix_nano: end,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let mut root = span(1, None, "POST /api/admin/login", 0, 5_000_000);
+        root.attributes.push(KeyValue {
+            key: "http.response.status_code".into(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::IntValue(500)),
+            }),
+        });
+        root.status = Some(Status {
+            message: "boom".into(),
+            code: StatusCode::Error as i32,
+        });
+        let spans = vec![
+            span(3, Some(2), "query", 2_000_000, 3_000_000),
+            root,
+            span(2, Some(1), "login", 1_000_000, 4_000_000),
+            span(4, Some(1), "verify", 4_000_000, 4_500_000),
+        ];
+
+        let out = render(TraceId::from_bytes([1; 16]), spans);
+        let expected = "\
+trace 01010101010101010101010101010101 · 4 spans · 5.000 ms
+└── POST /api/admin/login [server] 5.000 ms (+0.000 ms) http.response.status_code=500 ERROR: boom
+    ├── login [internal] 3.000 ms (+1.000 ms)
+    │   └── query [internal] 1.000 ms (+2.000 ms)
+    └── verify [internal] 0.500 ms (+4.000 ms)
+";
+        assert_eq!(out, expected);
+    }
+}
@@ -1,35 +1,95 @@
-use opentelemetry::{trace::TracerProvider, KeyValue};
-use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
-use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_semantic_conventions::resource::{SERVICE_NAME, SERVICE_VERSION};
+use std::time::Duration;
 
-/// 创建共享的 OTel Resource
-pub fn create_resource() -> Resource {
-    Resource::builder()
-        .with_attributes([
-            KeyValue::new(SERVICE_NAME, "axum-otel-demo"),
-            KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
-        ])
-        .build()
-}
+use opentelemetry_sdk::{
+    trace::{
+        BatchConfigBuilder, BatchSpanProcessor, SdkTracerProvider, SpanExporter, SpanProcessor,
+    },
+    Resource,
+};
+
+use super::enduser::{self, EnduserSpanProcessor};
+use super::file_export::FileSpanExporter;
+use super::resilience::{self, BoundedProcessor, ResilientExporter, RetryPolicy};
+use super::sampling::{RuleBasedSampler, TailSamplingProcessor};
+use super::zpages;
+use crate::config::TelemetryConfig;
 
-/// 初始化 OTel Tracer Provider，导出 spans 到 OTLP gRPC
+/// 初始化 OTel Tracer Provider，按采样配置导出 spans 到 OTLP 与本地文件
+///
+/// 两者都未启用时只在进程内生成 trace 上下文；启用 zpages 时 span 同时写入内存供查看
 pub fn init_tracer(
     resource: Resource,
-    endpoint: &str,
-) -> opentelemetry_sdk::trace::SdkTracer {
-    let exporter = opentelemetry_otlp::SpanExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint)
-        .build()
-        .expect("Failed to create OTLP span exporter");
+    config: &TelemetryConfig,
+    otlp_exporter: Option<opentelemetry_otlp::SpanExporter>,
+    file_exporter: Option<FileSpanExporter>,
+) -> SdkTracerProvider {
+    let mut builder = SdkTracerProvider::builder()
+        .with_sampler(RuleBasedSampler)
+        .with_resource(resource);
+    // 先补上 enduser.id，后续的导出与 zpages 处理器都能看到
+    if enduser::baggage_enabled() {
+        builder = builder.with_span_processor(EnduserSpanProcessor);
+    }
+    // OTLP 与文件导出共用一个尾部采样处理器，未采样的 span 只暂存一份
+    let mut exporters: Vec<Box<dyn SpanProcessor>> = Vec::new();
+    if let Some(exporter) = otlp_exporter {
+        exporters.push(Box::new(batch_processor(exporter, "otlp", config)));
+    }
+    if let Some(exporter) = file_exporter {
+        exporters.push(Box::new(batch_processor(exporter, "file", config)));
+    }
+    if !exporters.is_empty() {
+        builder = builder.with_span_processor(TailSamplingProcessor::new(exporters));
+    }
+    if let Some(processor) = zpages::processor() {
+        builder = builder.with_span_processor(processor);
+    }
+    builder.build()
+}
 
-    let provider = SdkTracerProvider::builder()
-        .with_batch_exporter(exporter)
-        .with_resource(resource)
-        .build();
+/// 浏览器端 spans 的导出处理器
+///
+/// 客户端已做过采样，不再经过尾部采样；导出时使用浏览器端的 Resource
+pub fn browser_processors(
+    resource: &Resource,
+    config: &TelemetryConfig,
+    otlp_exporter: Option<opentelemetry_otlp::SpanExporter>,
+    file_exporter: Option<FileSpanExporter>,
+) -> Vec<Box<dyn SpanProcessor>> {
+    let mut processors: Vec<Box<dyn SpanProcessor>> = Vec::new();
+    if let Some(exporter) = otlp_exporter {
+        processors.push(Box::new(batch_processor(exporter, "browser-otlp", config)));
+    }
+    if let Some(exporter) = file_exporter {
+        processors.push(Box::new(batch_processor(exporter, "browser-file", config)));
+    }
+    for processor in &mut processors {
+        processor.set_resource(resource);
+    }
+    processors
+}
 
-    let tracer = provider.tracer("axum-otel-demo");
-    opentelemetry::global::set_tracer_provider(provider);
-    tracer
+/// 有界队列计数 → 批处理 → 重试导出
+fn batch_processor<E: SpanExporter + 'static>(
+    exporter: E,
+    name: &'static str,
+    config: &TelemetryConfig,
+) -> BoundedProcessor<BatchSpanProcessor> {
+    let batch = &config.traces.batch;
+    let stats = resilience::register("traces", name, batch);
+    let exporter = ResilientExporter::new(
+        exporter,
+        RetryPolicy::new(&config.export_retry, batch),
+        stats.clone(),
+    );
+    let processor = BatchSpanProcessor::builder(exporter)
+        .with_batch_config(
+            BatchConfigBuilder::default()
+                .with_max_queue_size(batch.max_queue_size)
+                .with_max_export_batch_size(batch.max_export_batch_size)
+                .with_scheduled_delay(Duration::from_millis(batch.scheduled_delay_ms))
+                .build(),
+        )
+        .build();
+    BoundedProcessor::new(processor, stats)
 }
@@ -0,0 +1,563 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use opentelemetry::trace::{SpanId, SpanKind, Status, TraceId};
+use opentelemetry::{Context, KeyValue, Value};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_opentelemetry::OtelData;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::registry::LookupSpan;
+
+use super::console;
+use super::sampling::route_matches;
+use crate::config::ZPagesConfig;
+
+/// 单条 trace 最多保留的 span 数，避免个别大请求占满缓冲区
+const MAX_SPANS_PER_TRACE: usize = 512;
+
+static ZPAGES: OnceLock<ZPages> = OnceLock::new();
+
+/// 启用时创建内存缓冲区，须在构建 Tracer 与 subscriber 之前调用
+pub fn init(config: &ZPagesConfig) {
+    if config.enabled {
+        let _ = ZPAGES.set(ZPages {
+            traces: Mutex::new(TraceBuffer::default()),
+            errors: Mutex::new(VecDeque::new()),
+            logs: Mutex::new(VecDeque::new()),
+            max_traces: config.max_traces.max(1),
+            max_error_spans: config.max_error_spans.max(1),
+            max_logs: config.max_logs.max(1),
+        });
+    }
+}
+
+/// 挂到 TracerProvider 上的 span 处理器，未启用时返回 None
+pub fn processor() -> Option<ZPagesProcessor> {
+    ZPAGES.get().map(|_| ZPagesProcessor)
+}
+
+/// 收集日志事件的 tracing 层，未启用时返回 None
+pub fn log_layer() -> Option<LogLayer> {
+    ZPAGES.get().map(|_| LogLayer)
+}
+
+struct ZPages {
+    traces: Mutex<TraceBuffer>,
+    errors: Mutex<VecDeque<ErrorSpan>>,
+    logs: Mutex<VecDeque<LogRecord>>,
+    max_traces: usize,
+    max_error_spans: usize,
+    max_logs: usize,
+}
+
+/// 按首个 span 到达顺序淘汰最旧的 trace
+#[derive(Default)]
+struct TraceBuffer {
+    order: VecDeque<TraceId>,
+    traces: HashMap<TraceId, TraceEntry>,
+}
+
+#[derive(Default)]
+struct TraceEntry {
+    /// 本地根 span（server span），结束后该 trace 才可查询
+    root: Option<SpanRecord>,
+    spans: Vec<SpanRecord>,
+}
+
+/// 一条已完成的 trace，按根 span 汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceRecord {
+    pub trace_id: String,
+    pub name: String,
+    pub route: Option<String>,
+    pub status_code: Option<i64>,
+    pub start_time: String,
+    pub duration_ms: f64,
+    pub error: bool,
+    /// 按开始时间排序，包含根 span
+    pub spans: Vec<SpanRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanRecord {
+    pub trace_id: String,
+    pub span_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub kind: &'static str,
+    pub start_time: String,
+    pub duration_ms: f64,
+    /// unset / ok / error
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<String>,
+    pub attributes: BTreeMap<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<SpanEventRecord>,
+    #[serde(skip)]
+    start: SystemTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanEventRecord {
+    pub name: String,
+    pub time: String,
+    pub attributes: BTreeMap<String, serde_json::Value>,
+}
+
+/// 错误 span 及其所属请求的路由与状态码
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorSpan {
+    pub route: Option<String>,
+    pub status_code: Option<i64>,
+    #[serde(flatten)]
+    pub span: SpanRecord,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp: String,
+    pub level: &'static str,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, serde_json::Value>,
+    pub trace_id: Option<String>,
+    pub span_id: Option<String>,
+    pub route: Option<String>,
+    #[serde(skip)]
+    severity: Level,
+}
+
+/// 状态过滤：精确状态码（`500`）、状态码类别（`5xx`）或 span 出错（`error`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Code(i64),
+    Class(i64),
+    Error,
+}
+
+impl FromStr for StatusFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        if s == "error" {
+            return Ok(Self::Error);
+        }
+        if let Some(class) = s.strip_suffix("xx")
+            && let Ok(class @ 1..=5) = class.parse()
+        {
+            return Ok(Self::Class(class));
+        }
+        match s.parse() {
+            Ok(code @ 100..=599) => Ok(Self::Code(code)),
+            _ => Err(format!(
+                "无效的状态过滤 `{s}`，应为状态码、`5xx` 形式或 `error`"
+            )),
+        }
+    }
+}
+
+impl StatusFilter {
+    fn matches(self, status_code: Option<i64>, error: bool) -> bool {
+        match self {
+            Self::Code(code) => status_code == Some(code),
+            Self::Class(class) => status_code.is_some_and(|c| c / 100 == class),
+            Self::Error => error,
+        }
+    }
+}
+
+/// trace / 错误 span 的查询条件
+#[derive(Debug, Default)]
+pub struct TraceFilter {
+    /// 路由模板，以 `*` 结尾表示前缀匹配
+    pub route: Option<String>,
+    pub status: Option<StatusFilter>,
+    pub min_duration_ms: Option<f64>,
+}
+
+impl TraceFilter {
+    fn matches(
+        &self,
+        route: Option<&str>,
+        status_code: Option<i64>,
+        error: bool,
+        duration_ms: f64,
+    ) -> bool {
+        self.route
+            .as_deref()
+            .is_none_or(|pattern| route.is_some_and(|r| route_matches(pattern, r)))
+            && self.status.is_none_or(|s| s.matches(status_code, error))
+            && self.min_duration_ms.is_none_or(|min| duration_ms >= min)
+    }
+}
+
+/// 日志查询条件
+#[derive(Debug, Default)]
+pub struct LogFilter {
+    pub route: Option<String>,
+    /// 最低级别，如 `warn` 返回 WARN 与 ERROR
+    pub level: Option<Level>,
+    pub trace_id: Option<String>,
+}
+
+/// 最近完成的 trace，新的在前；未启用时返回 None
+pub fn traces(filter: &TraceFilter, limit: usize) -> Option<Vec<TraceRecord>> {
+    let zpages = ZPAGES.get()?;
+    let buffer = zpages.traces.lock().unwrap_or_else(|e| e.into_inner());
+    Some(
+        buffer
+            .order
+            .iter()
+            .rev()
+            .filter_map(|id| buffer.traces.get(id))
+            .filter_map(TraceEntry::to_record)
+            .filter(|t| filter.matches(t.route.as_deref(), t.status_code, t.error, t.duration_ms))
+            .take(limit)
+            .collect(),
+    )
+}
+
+/// 最近的错误 span，新的在前；不随 trace 淘汰
+pub fn error_spans(filter: &TraceFilter, limit: usize) -> Option<Vec<ErrorSpan>> {
+    let zpages = ZPAGES.get()?;
+    let errors = zpages.errors.lock().unwrap_or_else(|e| e.into_inner());
+    Some(
+        errors
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e.route.as_deref(), e.status_code, true, e.span.duration_ms))
+            .take(limit)
+            .cloned()
+            .collect(),
+    )
+}
+
+/// 最近的日志，新的在前
+pub fn logs(filter: &LogFilter, limit: usize) -> Option<Vec<LogRecord>> {
+    let zpages = ZPAGES.get()?;
+    let logs = zpages.logs.lock().unwrap_or_else(|e| e.into_inner());
+    Some(
+        logs.iter()
+            .rev()
+            .filter(|log| {
+                filter
+                    .route
+                    .as_deref()
+                    .is_none_or(|p| log.route.as_deref().is_some_and(|r| route_matches(p, r)))
+                    && filter.level.is_none_or(|level| log.severity <= level)
+                    && filter
+                        .trace_id
+                        .as_deref()
+                        .is_none_or(|id| log.trace_id.as_deref() == Some(id))
+            })
+            .take(limit)
+            .cloned()
+            .collect(),
+    )
+}
+
+impl TraceEntry {
+    fn to_record(&self) -> Option<TraceRecord> {
+        let root = self.root.as_ref()?;
+        let mut spans: Vec<SpanRecord> = self.spans.iter().chain([root]).cloned().collect();
+        spans.sort_by_key(|s| s.start);
+        Some(TraceRecord {
+            trace_id: root.trace_id.clone(),
+            name: root.name.clone(),
+            route: root.route(),
+            status_code: root.status_code(),
+            start_time: root.start_time.clone(),
+            duration_ms: root.duration_ms,
+            error: spans.iter().any(|s| s.status == "error"),
+            spans,
+        })
+    }
+}
+
+impl SpanRecord {
+    fn from_span_data(span: &SpanData) -> Self {
+        let duration = span
+            .end_time
+            .duration_since(span.start_time)
+            .unwrap_or_default();
+        let (status, status_message) = match &span.status {
+            Status::Unset => ("unset", None),
+            Status::Ok => ("ok", None),
+            Status::Error { description } => (
+                "error",
+                (!description.is_empty()).then(|| description.to_string()),
+            ),
+        };
+        Self {
+            trace_id: span.span_context.trace_id().to_string(),
+            span_id: span.span_context.span_id().to_string(),
+            parent_span_id: (span.parent_span_id != SpanId::INVALID)
+                .then(|| span.parent_span_id.to_string()),
+            name: span.name.to_string(),
+            kind: match span.span_kind {
+                SpanKind::Client => "client",
+                SpanKind::Server => "server",
+                SpanKind::Producer => "producer",
+                SpanKind::Consumer => "consumer",
+                SpanKind::Internal => "internal",
+            },
+            start_time: format_time(span.start_time),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            status,
+            status_message,
+            attributes: attributes_to_json(&span.attributes),
+            events: span
+                .events
+                .iter()
+                .map(|event| SpanEventRecord {
+                    name: event.name.to_string(),
+                    time: format_time(event.timestamp),
+                    attributes: attributes_to_json(&event.attributes),
+                })
+                .collect(),
+            start: span.start_time,
+        }
+    }
+
+    fn route(&self) -> Option<String> {
+        self.attributes
+            .get("http.route")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    }
+
+    fn status_code(&self) -> Option<i64> {
+        self.attributes
+            .get("http.response.status_code")
+            // tracing 的 u64 字段经 tracing-opentelemetry 转换后为字符串
+            .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+    }
+}
+
+/// 把每个结束的 span 写入内存缓冲区；收到 RecordOnly 的 span，不受采样结果影响
+#[derive(Debug)]
+pub struct ZPagesProcessor;
+
+impl SpanProcessor for ZPagesProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        let Some(zpages) = ZPAGES.get() else {
+            return;
+        };
+        let trace_id = span.span_context.trace_id();
+        let is_local_root =
+            span.span_kind == SpanKind::Server || span.parent_span_id == SpanId::INVALID;
+        let record = SpanRecord::from_span_data(&span);
+
+        let mut buffer = zpages.traces.lock().unwrap_or_else(|e| e.into_inner());
+        if !buffer.traces.contains_key(&trace_id) {
+            buffer.order.push_back(trace_id);
+            buffer.traces.insert(trace_id, TraceEntry::default());
+            while buffer.order.len() > zpages.max_traces {
+                if let Some(oldest) = buffer.order.pop_front() {
+                    buffer.traces.remove(&oldest);
+                }
+            }
+        }
+        let Some(entry) = buffer.traces.get_mut(&trace_id) else {
+            return;
+        };
+        if !is_local_root {
+            if entry.spans.len() < MAX_SPANS_PER_TRACE {
+                entry.spans.push(record);
+            }
+            return;
+        }
+
+        // 根 span 结束时整条 trace 已完整，按请求的路由与状态码归档其中的错误 span
+        let route = record.route();
+        let status_code = record.status_code();
+        let failed: Vec<ErrorSpan> = entry
+            .spans
+            .iter()
+            .chain([&record])
+            .filter(|s| s.status == "error")
+            .map(|s| ErrorSpan {
+                route: route.clone(),
+                status_code,
+                span: s.clone(),
+            })
+            .collect();
+        entry.root = Some(record);
+        drop(buffer);
+
+        if !failed.is_empty() {
+            let mut errors = zpages.errors.lock().unwrap_or_else(|e| e.into_inner());
+            errors.extend(failed);
+            while errors.len() > zpages.max_error_spans {
+                errors.pop_front();
+            }
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+/// 把日志事件连同所属 span 的 trace_id / span_id 与请求路由写入环形缓冲区
+pub struct LogLayer;
+
+impl<S> Layer<S> for LogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let Some(zpages) = ZPAGES.get() else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let span = ctx.event_span(event);
+        let ids = span.as_ref().and_then(console::span_ids);
+        // 路由取自最近的带 http.route 属性的祖先 span（请求 span）
+        let route = span.into_iter().flat_map(|s| s.scope()).find_map(|s| {
+            let extensions = s.extensions();
+            let attributes = extensions.get::<OtelData>()?.builder.attributes.as_ref()?;
+            attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == "http.route")
+                .map(|kv| kv.value.as_str().into_owned())
+        });
+
+        let metadata = event.metadata();
+        let record = LogRecord {
+            timestamp: format_time(SystemTime::now()),
+            level: metadata.level().as_str(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+            trace_id: ids.map(|(trace_id, _)| trace_id.to_string()),
+            span_id: ids.map(|(_, span_id)| span_id.to_string()),
+            route,
+            severity: *metadata.level(),
+        };
+
+        let mut logs = zpages.logs.lock().unwrap_or_else(|e| e.into_inner());
+        logs.push_back(record);
+        while logs.len() > zpages.max_logs {
+            logs.pop_front();
+        }
+    }
+}
+
+/// 收集事件字段，`message` 单独保存，`log.*` 兼容字段丢弃
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: serde_json::Value) {
+        match field.name() {
+            "message" => {
+                self.message = match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                }
+            }
+            name if name.starts_with("log.") => {}
+            name => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+fn attributes_to_json(attributes: &[KeyValue]) -> BTreeMap<String, serde_json::Value> {
+    attributes
+        .iter()
+        .map(|kv| {
+            let value = match &kv.value {
+                Value::Bool(b) => (*b).into(),
+                Value::I64(i) => (*i).into(),
+                Value::F64(f) => (*f).into(),
+                other => other.as_str().into_owned().into(),
+            };
+            (kv.key.to_string(), value)
+        })
+        .collect()
+}
+
+/// 北京时间 RFC 3339，精确到毫秒
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .with_timezone(&chrono_tz::Asia::Shanghai)
+        .to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_filter() {
+        assert_eq!("500".parse(), Ok(StatusFilter::Code(500)));
+        assert_eq!("5XX".parse(), Ok(StatusFilter::Class(5)));
+        assert_eq!("error".parse(), Ok(StatusFilter::Error));
+        assert!("9xx".parse::<StatusFilter>().is_err());
+        assert!("abc".parse::<StatusFilter>().is_err());
+
+        let filter = TraceFilter {
+            route: Some("/api/admin/*".to_string()),
+            status: Some(StatusFilter::Class(4)),
+            min_duration_ms: Some(10.0),
+        };
+        assert!(filter.matches(Some("/api/admin/login"), Some(401), false, 12.0));
+        assert!(!filter.matches(Some("/api/admin/login"), Some(200), false, 12.0));
+        assert!(!filter.matches(Some("/api/common/upload"), Some(401), false, 12.0));
+        assert!(!filter.matches(Some("/api/admin/login"), Some(401), false, 5.0));
+        assert!(!filter.matches(None, Some(401), false, 12.0));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 最多跟踪的客户端数，超出时淘汰最早创建的桶
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// 按键（如客户端 IP）计数的令牌桶限流器
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// 每秒补充的令牌数
+    rate: f64,
+    /// 桶容量，即允许的突发请求数
+    burst: f64,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    /// 按创建顺序排列的键
+    order: VecDeque<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn per_minute(limit: u32, burst: u32) -> Self {
+        Self {
+            rate: f64::from(limit) / 60.0,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// 取一个令牌，被限流时返回需等待的时间
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let Buckets { map, order } = &mut *buckets;
+        if !map.contains_key(key) {
+            while map.len() >= MAX_TRACKED_KEYS {
+                let Some(oldest) = order.pop_front() else {
+                    break;
+                };
+                map.remove(&oldest);
+            }
+            order.push_back(key.to_owned());
+        }
+
+        let bucket = map.entry(key.to_owned()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        *bucket = self.refill(*bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        if self.rate <= 0.0 {
+            return Err(Duration::from_secs(60));
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+    }
+
+    fn refill(&self, bucket: Bucket, now: Instant) -> Bucket {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        Bucket {
+            tokens: (bucket.tokens + elapsed * self.rate).min(self.burst),
+            updated: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_refill() {
+        let limiter = RateLimiter::per_minute(60, 2);
+        let start = Instant::now();
+        assert!(limiter.check_at("a", start).is_ok());
+        assert!(limiter.check_at("a", start).is_ok());
+        let wait = limiter.check_at("a", start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+        assert!(limiter.check_at("b", start).is_ok());
+        assert!(limiter.check_at("a", start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_tracked_keys_are_capped() {
+        let limiter = RateLimiter::per_minute(0, 1);
+        let start = Instant::now();
+        assert!(limiter.check_at("first", start).is_ok());
+        assert!(limiter.check_at("first", start).is_err());
+        for i in 1..MAX_TRACKED_KEYS {
+            assert!(limiter.check_at(&i.to_string(), start).is_ok());
+        }
+        assert!(limiter.check_at("last", start).is_ok());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.map.len(), MAX_TRACKED_KEYS);
+        assert_eq!(buckets.order.len(), MAX_TRACKED_KEYS);
+        assert!(!buckets.map.contains_key("first"));
+    }
+}